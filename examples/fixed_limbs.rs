@@ -0,0 +1,75 @@
+//! Compares the Fermat loop on malachite `Integer`s with the fixed-width `Fixed` limbs
+//! for typical RSA modulus sizes.
+//!
+//! Run with `cargo run --release --example fixed_limbs`.
+
+use diffsquare::factor::scan;
+use diffsquare::fixed::{FermatInt, Fixed};
+use diffsquare::sqrt::sqrt_ceil;
+use diffsquare::wheel::{Wheel, WHEEL_PRIMES};
+use malachite::{Integer, Natural};
+use std::time::{Duration, Instant};
+
+/// Number of `a` values scanned per measurement.
+const SPAN: u64 = 1 << 24;
+
+/// A pseudo-random odd number with exactly `bits` bits.
+fn modulus(bits: u64, seed: u64) -> Integer {
+    let mut state = seed;
+    let mut limbs: Vec<u64> = (0..bits / 64)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            state
+        })
+        .collect();
+    limbs[0] |= 1;
+    *limbs.last_mut().unwrap() |= 1 << 63;
+    Integer::from(Natural::from_limbs_asc(&limbs))
+}
+
+fn time<T: FermatInt>(n: &Integer, wheel: &Wheel) -> Duration {
+    let start = Instant::now();
+    let hit = scan::<T, _>(n, wheel, sqrt_ceil(n), SPAN, |_| true);
+    assert!(hit.is_none());
+    start.elapsed()
+}
+
+/// Average time of one batch update `a += t`, `x2 += t·(2a + t)`.
+fn time_shift<T: FermatInt>(n: &Integer) -> Duration {
+    const SHIFTS: u32 = 100_000;
+    let a0 = sqrt_ceil(n);
+    let mut a = T::from_integer(&a0).unwrap();
+    let mut x2 = T::from_integer(&(&a0 * &a0 - n)).unwrap();
+    let start = Instant::now();
+    for t in 0..SHIFTS {
+        T::shift(&mut a, &mut x2, 15015 + t as u64);
+    }
+    std::hint::black_box((a, x2));
+    start.elapsed() / SHIFTS
+}
+
+fn compare<const LIMBS: usize>(bits: u64) {
+    let n = modulus(bits, bits);
+    let wheel = Wheel::new(&n, WHEEL_PRIMES);
+    let malachite = time::<Integer>(&n, &wheel);
+    let fixed = time::<Fixed<LIMBS>>(&n, &wheel);
+    println!(
+        "{bits:>5} bits: scan malachite {:>6.2} ms, Fixed<{LIMBS}> {:>6.2} ms; \
+         shift malachite {:>5} ns, Fixed {:>5} ns",
+        malachite.as_secs_f64() * 1e3,
+        fixed.as_secs_f64() * 1e3,
+        time_shift::<Integer>(&n).as_nanos(),
+        time_shift::<Fixed<LIMBS>>(&n).as_nanos(),
+    );
+}
+
+fn main() {
+    println!("{SPAN} values of a per run");
+    compare::<4>(256);
+    compare::<6>(512);
+    compare::<10>(1024);
+    compare::<18>(2048);
+    compare::<34>(4096);
+}
@@ -0,0 +1,51 @@
+//! Compares the rejection rate and speed of the square pre-checks on Fermat candidates.
+//!
+//! Run with `cargo run --release --example qr_filter`.
+
+use diffsquare::legendre::{QR_11, QR_63, QR_64, QR_65, QR_ODD_MODULUS};
+use diffsquare::sqrt::{is_probably_square, legendre_probably_square, sqrt_ceil};
+use malachite::base::num::conversion::traits::WrappingFrom;
+use malachite::Integer;
+use std::time::Instant;
+
+const SAMPLES: u64 = 1_000_000;
+
+type Check = fn(&Integer) -> bool;
+
+fn bitsets_only(n: &Integer) -> bool {
+    let low = u64::wrapping_from(n);
+    let r = u64::wrapping_from(&(n % Integer::from(QR_ODD_MODULUS)));
+    QR_64.contains(low) && QR_63.contains(r) && QR_65.contains(r) && QR_11.contains(r)
+}
+
+fn main() {
+    println!("squares mod 64: {} of 64", QR_64.count());
+    println!("squares mod 63: {} of 63", QR_63.count());
+    println!("squares mod 65: {} of 65", QR_65.count());
+    println!("squares mod 11: {} of 11", QR_11.count());
+
+    // a² − n for consecutive a, the values the Fermat loop actually tests
+    let n = Integer::from(1020000000112780000002847u128);
+    let a0 = sqrt_ceil(&n);
+    let candidates: Vec<Integer> = (0..SAMPLES)
+        .map(|i| {
+            let a = &a0 + Integer::from(i);
+            &a * &a - &n
+        })
+        .collect();
+
+    let checks: [(&str, Check); 3] = [
+        ("legendre tables", legendre_probably_square),
+        ("bitsets only", bitsets_only),
+        ("bitsets + legendre", is_probably_square),
+    ];
+    for (name, check) in checks {
+        let start = Instant::now();
+        let passed = candidates.iter().filter(|x| check(x)).count();
+        let elapsed = start.elapsed();
+        println!(
+            "{name:>20}: {passed} of {SAMPLES} pass, {:.1} ns per candidate",
+            elapsed.as_nanos() as f64 / SAMPLES as f64
+        );
+    }
+}
@@ -0,0 +1,374 @@
+use crate::factor::{factor, PROGRESS_INTERVAL};
+use crate::legendre::{QR_11, QR_63, QR_64, QR_65, QR_ODD_MODULUS};
+use crate::prime::{is_probable_prime, SMALL_PRIMES};
+use crate::progress::{self, Progress};
+use crate::sqrt::{sqrt_ceil, sqrt_exact};
+use malachite::base::num::arithmetic::traits::{FloorSqrt, Square};
+use malachite::base::num::conversion::traits::WrappingFrom;
+use malachite::{Integer, Natural};
+use std::time::Instant;
+
+/// The big-integer arithmetic the generic Fermat search needs.
+///
+/// [`sqrt_exact`], [`sqrt_ceil`], [`difference_of_squares`] and [`factorize`] run
+/// entirely on these operations, so a caller that works with another big-integer type
+/// passes it in and gets it back without converting. The only conversion is [`to_integer`](Backend::to_integer), used for the
+/// primality check of each number to split and for the progress lines.
+///
+/// Implemented for malachite's `Integer` and `Natural`, and for num-bigint's `BigInt` and
+/// `BigUint` with the `num-bigint` feature.
+pub trait Backend: Clone + Ord {
+    /// The value `x`.
+    fn from_u64(x: u64) -> Self;
+
+    /// Converts to a malachite `Integer`.
+    fn to_integer(&self) -> Integer;
+
+    /// `self += other`.
+    fn add_assign(&mut self, other: &Self);
+
+    /// `self −= other`. `other` is never larger than `self`, so unsigned types may panic
+    /// otherwise.
+    fn sub_assign(&mut self, other: &Self);
+
+    /// `self += x`.
+    fn add_u64(&mut self, x: u64);
+
+    /// `self²`.
+    fn square(&self) -> Self;
+
+    /// `⌊√self⌋`, for `self ≥ 0`.
+    fn floor_sqrt(&self) -> Self;
+
+    /// `self mod m`, for `self ≥ 0`.
+    fn mod_u64(&self, m: u64) -> u64;
+
+    /// `⌊self / d⌋`, for `self ≥ 0`.
+    fn div_u64(&self, d: u64) -> Self;
+}
+
+impl Backend for Integer {
+    fn from_u64(x: u64) -> Self {
+        Integer::from(x)
+    }
+
+    fn to_integer(&self) -> Integer {
+        self.clone()
+    }
+
+    fn add_assign(&mut self, other: &Self) {
+        *self += other;
+    }
+
+    fn sub_assign(&mut self, other: &Self) {
+        *self -= other;
+    }
+
+    fn add_u64(&mut self, x: u64) {
+        *self += Integer::from(x);
+    }
+
+    fn square(&self) -> Self {
+        Square::square(self)
+    }
+
+    fn floor_sqrt(&self) -> Self {
+        FloorSqrt::floor_sqrt(self)
+    }
+
+    fn mod_u64(&self, m: u64) -> u64 {
+        u64::wrapping_from(&(self % Integer::from(m)))
+    }
+
+    fn div_u64(&self, d: u64) -> Self {
+        self / Integer::from(d)
+    }
+}
+
+impl Backend for Natural {
+    fn from_u64(x: u64) -> Self {
+        Natural::from(x)
+    }
+
+    fn to_integer(&self) -> Integer {
+        Integer::from(self)
+    }
+
+    fn add_assign(&mut self, other: &Self) {
+        *self += other;
+    }
+
+    fn sub_assign(&mut self, other: &Self) {
+        *self -= other;
+    }
+
+    fn add_u64(&mut self, x: u64) {
+        *self += Natural::from(x);
+    }
+
+    fn square(&self) -> Self {
+        Square::square(self)
+    }
+
+    fn floor_sqrt(&self) -> Self {
+        FloorSqrt::floor_sqrt(self)
+    }
+
+    fn mod_u64(&self, m: u64) -> u64 {
+        u64::wrapping_from(&(self % Natural::from(m)))
+    }
+
+    fn div_u64(&self, d: u64) -> Self {
+        self / Natural::from(d)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+mod num {
+    use super::Backend;
+    use malachite::{Integer, Natural};
+    use num_bigint::{BigInt, BigUint, Sign};
+
+    fn to_natural(x: &BigUint) -> Natural {
+        Natural::from_owned_limbs_asc(x.to_u64_digits())
+    }
+
+    fn low_digit(x: &BigUint) -> u64 {
+        x.to_u64_digits().first().copied().unwrap_or(0)
+    }
+
+    impl Backend for BigUint {
+        fn from_u64(x: u64) -> Self {
+            BigUint::from(x)
+        }
+
+        fn to_integer(&self) -> Integer {
+            Integer::from(to_natural(self))
+        }
+
+        fn add_assign(&mut self, other: &Self) {
+            *self += other;
+        }
+
+        fn sub_assign(&mut self, other: &Self) {
+            *self -= other;
+        }
+
+        fn add_u64(&mut self, x: u64) {
+            *self += x;
+        }
+
+        fn square(&self) -> Self {
+            self * self
+        }
+
+        fn floor_sqrt(&self) -> Self {
+            self.sqrt()
+        }
+
+        fn mod_u64(&self, m: u64) -> u64 {
+            low_digit(&(self % m))
+        }
+
+        fn div_u64(&self, d: u64) -> Self {
+            self / d
+        }
+    }
+
+    impl Backend for BigInt {
+        fn from_u64(x: u64) -> Self {
+            BigInt::from(x)
+        }
+
+        fn to_integer(&self) -> Integer {
+            let magnitude = Integer::from(to_natural(self.magnitude()));
+            match self.sign() {
+                Sign::Minus => -magnitude,
+                _ => magnitude,
+            }
+        }
+
+        fn add_assign(&mut self, other: &Self) {
+            *self += other;
+        }
+
+        fn sub_assign(&mut self, other: &Self) {
+            *self -= other;
+        }
+
+        fn add_u64(&mut self, x: u64) {
+            *self += x;
+        }
+
+        fn square(&self) -> Self {
+            self * self
+        }
+
+        fn floor_sqrt(&self) -> Self {
+            self.sqrt()
+        }
+
+        fn mod_u64(&self, m: u64) -> u64 {
+            low_digit((self % m).magnitude())
+        }
+
+        fn div_u64(&self, d: u64) -> Self {
+            self / d
+        }
+    }
+}
+
+/// The quick squareness screen of [`is_probably_square`](crate::sqrt::is_probably_square)
+/// for any [`Backend`]: the tables modulo 64, 63, 65 and 11, from two remainders.
+fn could_be_square<T: Backend>(x: &T) -> bool {
+    if !QR_64.contains(x.mod_u64(64)) {
+        return false;
+    }
+    let r = x.mod_u64(QR_ODD_MODULUS);
+    QR_63.contains(r) && QR_65.contains(r) && QR_11.contains(r)
+}
+
+/// Attempts to factor `n` using Fermat's Difference of Squares method, computing in `T`.
+///
+/// The search and its result are the same as with
+/// [`factor::difference_of_squares`](crate::factor::difference_of_squares), but every
+/// step runs on `T`. For malachite `Integer`s the `factor` version is faster: it adds the
+/// residue wheel and the machine-word and fixed-limb loops.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `iteration` - A mutable counter tracking the number of iterations attempted.
+/// * `prec` - Precision used when printing intermediate values in scientific notation (if not in quiet mode).
+/// * `quiet` - If `true`, suppresses all prompts and intermediate output.
+///
+/// # Returns
+/// `Some((p, q))` if a non-trivial factor pair is found, otherwise `None`.
+/// Primes are detected up front with [`is_probable_prime`] and return `None` immediately.
+pub fn difference_of_squares<T: Backend>(
+    n: &T,
+    iteration: &mut T,
+    prec: u64,
+    quiet: bool,
+) -> Option<(T, T)> {
+    if is_probable_prime(&n.to_integer()) {
+        return None;
+    }
+
+    let started = Instant::now();
+    let progress = progress::terminal(prec, quiet);
+    let one = T::from_u64(1);
+    let mut a = sqrt_ceil(n);
+    if *iteration > one {
+        a.add_assign(iteration);
+        a.sub_assign(&one);
+    } else if *iteration < one {
+        *iteration = one.clone();
+    }
+    let mut x2 = a.square();
+    x2.sub_assign(n);
+
+    // The search state at `a` with `x = ⌊√(a² − n)⌋`, in the malachite types of `Progress`.
+    let at = |offset: u64, a: &T, x: &T, send: &dyn Fn(&Progress)| {
+        let (a, x) = (a.to_integer(), x.to_integer());
+        let (p, q) = factor(&a, &x, Integer::from(1), Integer::from(1));
+        send(&Progress {
+            iteration: &current_iteration(iteration, offset).to_integer(),
+            a: &a,
+            p: &p,
+            q: &q,
+            elapsed: started.elapsed(),
+        });
+    };
+
+    let mut offset = 0u64;
+    while a < *n {
+        if could_be_square(&x2) {
+            let (is_square, x) = sqrt_exact(&x2);
+            if is_square {
+                let (mut p, mut q) = (a.clone(), a.clone());
+                p.sub_assign(&x);
+                q.add_assign(&x);
+                if p == one {
+                    *iteration = current_iteration(iteration, offset);
+                    return None;
+                }
+                at(offset, &a, &x, &|progress_at| progress.found(progress_at));
+                *iteration = current_iteration(iteration, offset);
+                return Some((p, q));
+            }
+        }
+        if !quiet && offset.is_multiple_of(PROGRESS_INTERVAL) {
+            at(offset, &a, &x2.floor_sqrt(), &|progress_at| {
+                progress.update(progress_at)
+            });
+        }
+
+        x2.add_assign(&a);
+        x2.add_assign(&a);
+        x2.add_u64(1);
+        a.add_u64(1);
+        offset += 1;
+    }
+    *iteration = current_iteration(iteration, offset);
+    None
+}
+
+/// `iteration + offset`.
+fn current_iteration<T: Backend>(iteration: &T, offset: u64) -> T {
+    let mut current = iteration.clone();
+    current.add_u64(offset);
+    current
+}
+
+/// Computes the complete prime factorization of `n` with [`difference_of_squares`],
+/// computing in `T`.
+///
+/// As in [`factor::factorize`](crate::factor::factorize), the primes in
+/// [`SMALL_PRIMES`] are removed by trial division first, and every remaining composite is
+/// split with Fermat until only primes are left.
+///
+/// # Returns
+/// The prime factors of `n` with their exponents, sorted by prime.
+pub fn factorize<T: Backend>(n: &T, iterations: &mut T, prec: u64, quiet: bool) -> Vec<(T, u64)> {
+    let mut primes: Vec<T> = Vec::new();
+    if *n < T::from_u64(2) {
+        return Vec::new();
+    }
+
+    let mut rest = n.clone();
+    for &p in SMALL_PRIMES {
+        while rest.mod_u64(p) == 0 {
+            rest = rest.div_u64(p);
+            primes.push(T::from_u64(p));
+        }
+    }
+
+    let mut composites: Vec<T> = Vec::new();
+    if rest > T::from_u64(1) {
+        composites.push(rest);
+    }
+
+    while let Some(m) = composites.pop() {
+        if is_probable_prime(&m.to_integer()) {
+            primes.push(m);
+            continue;
+        }
+        let mut iteration = T::from_u64(0);
+        // Every odd composite is a difference of two squares.
+        let (p, q) = difference_of_squares(&m, &mut iteration, prec, quiet)
+            .expect("Fermat splits every odd composite");
+        iterations.add_assign(&iteration);
+        composites.push(p);
+        composites.push(q);
+    }
+
+    primes.sort();
+    let mut factors: Vec<(T, u64)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, exponent)) if *last == p => *exponent += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
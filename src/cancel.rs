@@ -0,0 +1,63 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A cooperative cancellation signal shared between a caller and running searches.
+///
+/// Clones share the same signal. The factoring methods check
+/// [`is_cancelled`](CancelToken::is_cancelled) between batches of work and return early
+/// once [`cancel`](CancelToken::cancel) has been called or the deadline has passed. The
+/// default token is never cancelled.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    flag: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// A token that is only cancelled by [`cancel`](CancelToken::cancel).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that is also cancelled once `deadline` has passed.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        CancelToken {
+            flag: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// A token that is also cancelled `timeout` from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Asks every search holding this token to stop.
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the deadline, if any, has passed.
+    pub fn timed_out(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Whether the searches should stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed) || self.timed_out()
+    }
+
+    /// A token sharing this one's flag, with a deadline `timeout` from now or this
+    /// token's own deadline, whichever comes first.
+    ///
+    /// Cancelling either token stops both, but the new deadline only applies to the new token.
+    pub fn limited(&self, timeout: Duration) -> CancelToken {
+        let deadline = Instant::now() + timeout;
+        CancelToken {
+            flag: Arc::clone(&self.flag),
+            deadline: Some(self.deadline.map_or(deadline, |own| own.min(deadline))),
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crate::fermat::FermatState;
+use crate::progress::{Progress, ProgressSink};
+use crate::sqrt::sqrt_ceil;
+use malachite::base::num::basic::traits::One;
+use malachite::Integer;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A saved position of a Fermat search, written to disk so a long run survives a restart.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The number being factored.
+    pub n: Integer,
+    /// The next `a` to test, in the number the search runs on (`4uvn` for a ratio search).
+    pub a: Integer,
+    /// The iteration of `a`: the start iteration to resume with.
+    pub iteration: Integer,
+    /// The iteration the saved run began at, carried over from any checkpoint it resumed.
+    /// `1` means every `a` from `⌈√n⌉` up to `a` has been ruled out.
+    pub first: Integer,
+    /// The method that wrote the checkpoint, such as `fermat` or `ratio-fermat 3/2`.
+    pub method: String,
+    /// Seconds since the Unix epoch when the checkpoint was taken.
+    pub timestamp: u64,
+}
+
+/// The JSON layout of a checkpoint file, with the integers in decimal.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    n: String,
+    a: String,
+    iteration: String,
+    #[serde(default)]
+    first: Option<String>,
+    method: String,
+    timestamp: u64,
+}
+
+impl Checkpoint {
+    /// A checkpoint of the search for `n` at `a` and `iteration`, taken now, of a run
+    /// that began at iteration `first`.
+    pub fn new(
+        n: &Integer,
+        a: &Integer,
+        iteration: &Integer,
+        first: &Integer,
+        method: &str,
+    ) -> Self {
+        Checkpoint {
+            n: n.clone(),
+            a: a.clone(),
+            iteration: iteration.clone(),
+            first: first.clone(),
+            method: method.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs()),
+        }
+    }
+
+    /// The state of a plain Fermat search, for
+    /// [`FermatSearch::from_state`](crate::fermat::FermatSearch::from_state).
+    pub fn state(&self) -> FermatState {
+        FermatState {
+            n: self.n.clone(),
+            a: self.a.clone(),
+            iteration: self.iteration.clone(),
+            start: &self.a - (&self.iteration - &self.first),
+        }
+    }
+
+    /// Whether `a` is the `iteration`-th value of a Fermat search on `multiplier · n`, that
+    /// is `a = ⌈√(multiplier · n)⌉ + iteration − 1`, and the run began no later than
+    /// `iteration`. A checkpoint that fails this was edited or corrupted, and resuming
+    /// from it would skip or repeat part of the search.
+    ///
+    /// # Arguments
+    /// * `multiplier` - `1` for plain Fermat, or [`Ratio::multiplier`](crate::ratio::Ratio::multiplier).
+    pub fn is_consistent(&self, multiplier: &Integer) -> bool {
+        Integer::ONE <= self.first
+            && self.first <= self.iteration
+            && self.a == sqrt_ceil(&(&self.n * multiplier)) + &self.iteration - Integer::ONE
+    }
+
+    /// Writes the checkpoint to `path` as JSON.
+    ///
+    /// The file is written next to `path` first and then renamed over it, so a crash
+    /// mid-write leaves the previous checkpoint intact.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let record = Record {
+            n: self.n.to_string(),
+            a: self.a.to_string(),
+            iteration: self.iteration.to_string(),
+            first: Some(self.first.to_string()),
+            method: self.method.clone(),
+            timestamp: self.timestamp,
+        };
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".tmp");
+        fs::write(&partial, serde_json::to_string_pretty(&record)?)?;
+        fs::rename(&partial, path)
+    }
+
+    /// Reads a checkpoint written by [`save`](Checkpoint::save).
+    ///
+    /// A file without `first` is taken to have begun at its own `iteration`, which claims
+    /// nothing about the `a` values before it.
+    ///
+    /// # Returns
+    /// The checkpoint, or an [`io::ErrorKind::InvalidData`] error if the file is not one.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let record: Record = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let integer = |field: &str, value: &str| {
+            Integer::from_str(value).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid {} in checkpoint: {:?}", field, value),
+                )
+            })
+        };
+        let iteration = integer("iteration", &record.iteration)?;
+        Ok(Checkpoint {
+            n: integer("n", &record.n)?,
+            a: integer("a", &record.a)?,
+            first: match record.first {
+                Some(first) => integer("first", &first)?,
+                None => iteration.clone(),
+            },
+            iteration,
+            method: record.method,
+            timestamp: record.timestamp,
+        })
+    }
+}
+
+/// A [`ProgressSink`] that saves a [`Checkpoint`] from the progress updates at most once
+/// per interval, and passes every update on to another sink.
+///
+/// Write errors do not stop the search; they are reported on stderr and the next update
+/// tries again.
+pub struct CheckpointSink<'a> {
+    path: PathBuf,
+    every: Duration,
+    n: Integer,
+    first: Integer,
+    method: String,
+    last: Mutex<Instant>,
+    inner: &'a (dyn ProgressSink + Sync),
+}
+
+impl<'a> CheckpointSink<'a> {
+    /// Saves the search for `n` by `method`, which began at iteration `first`, to `path`
+    /// every `every`, starting one interval from now.
+    pub fn new(
+        path: &Path,
+        every: Duration,
+        n: &Integer,
+        first: &Integer,
+        method: &str,
+        inner: &'a (dyn ProgressSink + Sync),
+    ) -> Self {
+        CheckpointSink {
+            path: path.to_path_buf(),
+            every,
+            n: n.clone(),
+            first: first.clone(),
+            method: method.to_string(),
+            last: Mutex::new(Instant::now()),
+            inner,
+        }
+    }
+}
+
+impl ProgressSink for CheckpointSink<'_> {
+    fn update(&self, progress: &Progress) {
+        self.inner.update(progress);
+        let mut last = self.last.lock().unwrap();
+        if last.elapsed() < self.every {
+            return;
+        }
+        *last = Instant::now();
+        let checkpoint = Checkpoint::new(
+            &self.n,
+            progress.a,
+            progress.iteration,
+            &self.first,
+            &self.method,
+        );
+        if let Err(e) = checkpoint.save(&self.path) {
+            eprintln!(
+                "⚠️  Failed to write checkpoint {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    fn found(&self, progress: &Progress) {
+        self.inner.found(progress);
+    }
+}
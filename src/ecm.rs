@@ -0,0 +1,276 @@
+use crate::cancel::CancelToken;
+use crate::pm1::{max_power, pair, EXPONENT_BITS, STAGE2_D};
+use crate::prime::{is_probable_prime, primes_up_to};
+use malachite::base::num::arithmetic::traits::{
+    Gcd, ModAdd, ModInverse, ModMul, ModSquare, ModSub, Parity,
+};
+use malachite::base::num::basic::traits::{One, Two};
+use malachite::base::num::logic::traits::{BitAccess, SignificantBits};
+use malachite::{Integer, Natural};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Default number of curves.
+pub const DEFAULT_CURVES: u64 = 100;
+
+/// Number of stage 2 primes multiplied together between GCDs.
+const STAGE2_BATCH: u64 = 1024;
+
+/// A projective point `(X : Z)` on a Montgomery curve; `Y` is never needed.
+#[derive(Clone, Debug)]
+pub struct Point {
+    pub x: Natural,
+    pub z: Natural,
+}
+
+/// A Montgomery curve `By² = x³ + Ax² + x` modulo `n`, stored as `(A + 2) / 4`.
+#[derive(Clone, Debug)]
+pub struct Curve<'a> {
+    pub n: &'a Natural,
+    pub a24: Natural,
+}
+
+/// Outcome of building a curve from a Suyama parameter.
+pub enum Setup<'a> {
+    /// A usable curve and its starting point.
+    Curve(Curve<'a>, Point),
+    /// Inverting the curve constant exposed a factor of `n`.
+    Factor(Natural),
+    /// The parameter is degenerate modulo `n`.
+    Degenerate,
+}
+
+impl<'a> Curve<'a> {
+    /// Builds the curve and starting point for Suyama's parametrization with parameter `sigma ≥ 6`:
+    /// `u = σ² − 5`, `v = 4σ`, `P = (u³ : v³)` and `(A + 2) / 4 = (v − u)³(3u + v) / (16u³v)`.
+    pub fn suyama(sigma: u64, n: &'a Natural) -> Setup<'a> {
+        let sigma = Natural::from(sigma) % n;
+        let u = (&sigma).mod_square(n).mod_sub(Natural::from(5u32) % n, n);
+        let v = (sigma << 2u32) % n;
+        let u3 = (&u).mod_square(n).mod_mul(&u, n);
+        let v3 = (&v).mod_square(n).mod_mul(&v, n);
+
+        let v_minus_u = (&v).mod_sub(&u, n);
+        let numerator = (&v_minus_u)
+            .mod_square(n)
+            .mod_mul(&v_minus_u, n)
+            .mod_mul((&u).mod_mul(Natural::from(3u32) % n, n).mod_add(&v, n), n);
+        let denominator = (&u3).mod_mul(&v, n).mod_mul(Natural::from(16u32) % n, n);
+
+        match (&denominator).mod_inverse(n) {
+            Some(inverse) => Setup::Curve(
+                Curve {
+                    n,
+                    a24: numerator.mod_mul(inverse, n),
+                },
+                Point { x: u3, z: v3 },
+            ),
+            None => {
+                let g = denominator.gcd(n);
+                if g != Natural::ONE && g != *n {
+                    Setup::Factor(g)
+                } else {
+                    Setup::Degenerate
+                }
+            }
+        }
+    }
+
+    /// `[2]P`.
+    pub fn double(&self, p: &Point) -> Point {
+        let n = self.n;
+        let sum = (&p.x).mod_add(&p.z, n).mod_square(n);
+        let diff = (&p.x).mod_sub(&p.z, n).mod_square(n);
+        let t = (&sum).mod_sub(&diff, n);
+        Point {
+            x: sum.mod_mul(&diff, n),
+            z: (&t).mod_mul(diff.mod_add((&self.a24).mod_mul(&t, n), n), n),
+        }
+    }
+
+    /// `P + Q`, given `P − Q`.
+    pub fn add(&self, p: &Point, q: &Point, diff: &Point) -> Point {
+        let n = self.n;
+        let u = (&p.x).mod_sub(&p.z, n).mod_mul((&q.x).mod_add(&q.z, n), n);
+        let v = (&p.x).mod_add(&p.z, n).mod_mul((&q.x).mod_sub(&q.z, n), n);
+        Point {
+            x: (&diff.z).mod_mul((&u).mod_add(&v, n).mod_square(n), n),
+            z: (&diff.x).mod_mul(u.mod_sub(v, n).mod_square(n), n),
+        }
+    }
+
+    /// `[k]P` with the Montgomery ladder. `k` must be positive.
+    pub fn multiply(&self, k: &Natural, p: &Point) -> Point {
+        let mut r0 = p.clone();
+        let mut r1 = self.double(p);
+        for bit in (0..k.significant_bits() - 1).rev() {
+            if k.get_bit(bit) {
+                r0 = self.add(&r1, &r0, p);
+                r1 = self.double(&r1);
+            } else {
+                r1 = self.add(&r0, &r1, p);
+                r0 = self.double(&r0);
+            }
+        }
+        r0
+    }
+}
+
+/// Runs stage 1 and stage 2 on a single curve and returns a non-trivial factor if one shows up.
+///
+/// `primes` must hold every prime up to `max(b1, b2)`. The curve is abandoned, with
+/// `None`, between exponent batches and stage 2 GCDs once `cancel` fires.
+pub fn run_curve(
+    n: &Natural,
+    sigma: u64,
+    b1: u64,
+    b2: u64,
+    primes: &[u64],
+    cancel: &CancelToken,
+) -> Option<Natural> {
+    let (curve, mut point) = match Curve::suyama(sigma, n) {
+        Setup::Curve(curve, point) => (curve, point),
+        Setup::Factor(g) => return Some(g),
+        Setup::Degenerate => return None,
+    };
+    let check = |z: &Natural| {
+        let g = z.gcd(n);
+        (g != Natural::ONE && g != *n).then_some(g)
+    };
+
+    // Stage 1
+    let stage1_end = primes.partition_point(|&p| p <= b1);
+    let mut start = 0;
+    while start < stage1_end {
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let mut end = start;
+        let mut exponent = Natural::ONE;
+        while end < stage1_end && exponent.significant_bits() < EXPONENT_BITS {
+            exponent *= Natural::from(max_power(primes[end], b1));
+            end += 1;
+        }
+        point = curve.multiply(&exponent, &point);
+        start = end;
+    }
+    let g = (&point.z).gcd(n);
+    if g == *n {
+        return None;
+    }
+    if g != Natural::ONE {
+        return Some(g);
+    }
+    if b2 <= b1 {
+        return None;
+    }
+
+    // Stage 2: every prime q = kD ± j with j < D/2 is caught by X_{kD}·Z_j − X_j·Z_{kD}.
+    let half = STAGE2_D / 2;
+    let mut baby: Vec<Option<Point>> = vec![None; half as usize + 1];
+    let doubled = curve.double(&point);
+    let mut prev = point.clone();
+    let mut cur = curve.add(&doubled, &point, &point);
+    baby[1] = Some(point.clone());
+    for j in (3..=half).step_by(2) {
+        if j.gcd(STAGE2_D) == 1 {
+            baby[j as usize] = Some(cur.clone());
+        }
+        let next = curve.add(&cur, &doubled, &prev);
+        prev = cur;
+        cur = next;
+    }
+
+    let step = curve.multiply(&Natural::from(STAGE2_D), &point);
+    let mut k = ((b1 + half) / STAGE2_D).max(1);
+    let mut giant = curve.multiply(&Natural::from(k * STAGE2_D), &point);
+    let mut giant_next = curve.multiply(&Natural::from((k + 1) * STAGE2_D), &point);
+
+    let mut acc = Natural::ONE;
+    let mut batched = 0;
+    for &q in &primes[stage1_end..] {
+        if q > b2 {
+            break;
+        }
+        if q <= half {
+            acc = acc.mod_mul(curve.multiply(&Natural::from(q), &point).z, n);
+        } else {
+            while q > k * STAGE2_D + half {
+                let next = curve.add(&giant_next, &step, &giant);
+                giant = std::mem::replace(&mut giant_next, next);
+                k += 1;
+            }
+            let j = q.abs_diff(k * STAGE2_D);
+            if let Some(b) = &baby[j as usize] {
+                let term = (&giant.x)
+                    .mod_mul(&b.z, n)
+                    .mod_sub((&b.x).mod_mul(&giant.z, n), n);
+                acc = acc.mod_mul(term, n);
+            }
+        }
+
+        batched += 1;
+        if batched == STAGE2_BATCH {
+            batched = 0;
+            if let Some(g) = check(&acc) {
+                return Some(g);
+            }
+            if cancel.is_cancelled() {
+                return None;
+            }
+        }
+    }
+    check(&acc)
+}
+
+/// Attempts to factor `n` using Lenstra's elliptic curve method.
+///
+/// Curves are Montgomery curves from Suyama's parametrization with `σ = seed + 6, seed + 7, ...`.
+/// Each curve runs stage 1 up to `b1` and a baby-step/giant-step stage 2 up to `b2`.
+/// Curves are spread over the current rayon thread pool, and the first factor found
+/// stops the remaining curves from being scheduled.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `b1` - Stage 1 bound.
+/// * `b2` - Stage 2 bound. Stage 2 is skipped if `b2 ≤ b1`.
+/// * `curves` - Maximum number of curves to try.
+/// * `seed` - Offset for the Suyama parameters.
+/// * `iteration` - A mutable counter tracking the number of curves run.
+/// * `cancel` - Stops the curves in progress and keeps new ones from starting once cancelled.
+///
+/// # Returns
+/// `Some((p, q))` with `p ≤ q` if a non-trivial factor pair is found, otherwise `None`.
+pub fn ecm(
+    n: &Integer,
+    b1: u64,
+    b2: u64,
+    curves: u64,
+    seed: u64,
+    iteration: &mut Integer,
+    cancel: &CancelToken,
+) -> Option<(Integer, Integer)> {
+    if *n < 4 || is_probable_prime(n) {
+        return None;
+    }
+    let n = n.unsigned_abs_ref();
+    if n.even() {
+        return Some((Integer::TWO, Integer::from(n >> 1)));
+    }
+
+    let primes = primes_up_to(b1.max(b2));
+    let completed = AtomicU64::new(0);
+    let factor = (0..curves).into_par_iter().find_map_any(|i| {
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let g = run_curve(n, seed + 6 + i, b1, b2, &primes, cancel);
+        if g.is_some() || !cancel.is_cancelled() {
+            completed.fetch_add(1, Ordering::Relaxed);
+        }
+        g
+    });
+    *iteration += Integer::from(completed.into_inner());
+
+    factor.map(|g| pair(g, n))
+}
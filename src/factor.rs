@@ -1,16 +1,29 @@
-use crate::sqrt::{is_probably_square, sqrt_ceil, sqrt_exact};
+use crate::cancel::CancelToken;
+use crate::fixed::{FermatInt, Fixed};
+use crate::native;
+use crate::prime::{is_probable_prime, SMALL_PRIMES};
+use crate::progress::{self, Progress, ProgressSink};
+use crate::sqrt::{sqrt_ceil, sqrt_exact, SquareFilter};
+use crate::squfof::{squfof, MAX_BITS};
+use crate::wheel::{Wheel, WHEEL_PRIMES};
 use malachite::{
     base::{
         num::{
-            arithmetic::traits::Square,
-            basic::traits::{One, Two},
-            conversion::{string::options::ToSciOptions, traits::ToSci},
+            arithmetic::traits::{FloorSqrt, Square},
+            basic::traits::{One, Two, Zero},
+            conversion::{
+                string::options::ToSciOptions,
+                traits::{ToSci, WrappingFrom},
+            },
+            logic::traits::SignificantBits,
         },
         rounding_modes::RoundingMode::Floor,
     },
     Integer,
 };
-use std::{io, io::Write};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 
 pub fn scinot(n: &Integer, prec: u64) {
     let mut options = ToSciOptions::default();
@@ -30,7 +43,118 @@ pub fn verbose(iteration: &Integer, p: &Integer, q: &Integer, prec: u64) {
 }
 
 pub fn factor(a: &Integer, x: &Integer, p: Integer, q: Integer) -> (Integer, Integer) {
-    return ((a - x) / p, (a + x) / q);
+    ((a - x) / p, (a + x) / q)
+}
+
+/// Limits on a Fermat search, for inputs where it may otherwise run practically forever.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchLimit {
+    /// Last iteration to run; the search stops after it.
+    pub max_iterations: Option<Integer>,
+    /// Only look for factors with `q − p ≤ 2^max_gap_bits`, which bounds `a` by
+    /// `√(n + 4^(max_gap_bits − 1))`.
+    pub max_gap_bits: Option<u64>,
+}
+
+impl SearchLimit {
+    /// The first `a` the search must not reach, when `a0` is tested at iteration `first`.
+    pub fn end(&self, n: &Integer, a0: &Integer, first: &Integer) -> Integer {
+        let mut end = n.clone();
+        if let Some(max) = &self.max_iterations {
+            end = end.min(a0 + max - first + Integer::ONE);
+        }
+        if let Some(bits) = self.max_gap_bits {
+            // q − p = 2x ≤ 2^bits
+            let x_max = if bits == 0 {
+                Integer::ZERO
+            } else {
+                Integer::ONE << (bits - 1)
+            };
+            end = end.min((n + x_max.square()).floor_sqrt() + Integer::ONE);
+        }
+        end.max(a0.clone())
+    }
+}
+
+/// The result of a bounded Fermat search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FermatOutcome {
+    /// A non-trivial factor pair `(p, q)`.
+    Found(Integer, Integer),
+    /// The search hit its [`SearchLimit`] first, with every `a` in the [`Searched`] range
+    /// ruled out.
+    Exhausted { searched: Searched },
+    /// The [`CancelToken`] stopped the search, with the same guarantee and the same
+    /// resumable `iteration` as [`FermatOutcome::Exhausted`].
+    Cancelled { searched: Searched },
+    /// `n` is prime, or has no split into two factors of the same parity.
+    NoFactor,
+}
+
+/// The `a` values a stopped Fermat search ruled out: `start ≤ a < end`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Searched {
+    /// The first `a` tested.
+    pub start: Integer,
+    /// The first `a` not tested.
+    pub end: Integer,
+    /// The proven lower bound on `q − p` for every factor pair `p ≤ q` of the same parity.
+    /// Only known when `start` is `⌈√n⌉`: a search that began later may have skipped a
+    /// pair with a smaller gap.
+    pub min_gap: Option<Integer>,
+}
+
+impl Searched {
+    /// The range `[start, end)` searched for `n`, with [`min_gap`](Searched::min_gap) if it
+    /// begins at `⌈√n⌉`.
+    pub fn new(n: &Integer, start: Integer, end: Integer) -> Self {
+        let min_gap = (start <= sqrt_ceil(n)).then(|| min_gap(n, &end));
+        Searched {
+            start,
+            end,
+            min_gap,
+        }
+    }
+}
+
+impl From<Option<(Integer, Integer)>> for FermatOutcome {
+    fn from(pair: Option<(Integer, Integer)>) -> Self {
+        match pair {
+            Some((p, q)) => FermatOutcome::Found(p, q),
+            None => FermatOutcome::NoFactor,
+        }
+    }
+}
+
+impl FermatOutcome {
+    /// The factor pair, if one was found.
+    pub fn found(self) -> Option<(Integer, Integer)> {
+        match self {
+            FermatOutcome::Found(p, q) => Some((p, q)),
+            _ => None,
+        }
+    }
+}
+
+/// The proven lower bound on `q − p` once every `a < end` is ruled out: `a = (p + q)/2 ≥ end`,
+/// so `(q − p)/2 = √(a² − n) ≥ √(end² − n)`.
+pub(crate) fn min_gap(n: &Integer, end: &Integer) -> Integer {
+    sqrt_ceil(&(end.square() - n)) << 1u32
+}
+
+/// The outcome of a search from `first` cancelled at `a0 + offset`, after moving
+/// `iteration` past every `a` that was ruled out.
+fn cancelled(
+    n: &Integer,
+    iteration: &mut Integer,
+    first: &Integer,
+    a0: &Integer,
+    offset: u64,
+) -> FermatOutcome {
+    *iteration += Integer::from(offset);
+    FermatOutcome::Cancelled {
+        searched: Searched::new(n, first.clone(), a0 + Integer::from(offset)),
+    }
 }
 
 /// Attempts to factor the given number `n` using Fermat’s Difference of Squares method.
@@ -46,14 +170,50 @@ pub fn factor(a: &Integer, x: &Integer, p: Integer, q: Integer) -> (Integer, Int
 ///
 /// # Returns
 /// `Some((p, q))` if a non-trivial factor pair is found, otherwise `None`.
+/// Primes are detected up front with [`is_probable_prime`] and return `None` immediately.
+///
+/// Inputs below `2^64` run on machine words through [`native::scan`]. Larger inputs up
+/// to about 4096 bits keep `a` and `a² − n` in stack-allocated [`Fixed`] limbs.
 pub fn difference_of_squares(
     n: &Integer,
     iteration: &mut Integer,
     prec: u64,
     quiet: bool,
 ) -> Option<(Integer, Integer)> {
+    let limit = SearchLimit::default();
+    let progress = progress::terminal(prec, quiet);
+    bounded_difference_of_squares(n, iteration, &limit, &CancelToken::new(), &*progress).found()
+}
+
+/// [`difference_of_squares`] with a [`SearchLimit`], a [`CancelToken`] and a
+/// [`ProgressSink`].
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `iteration` - A mutable counter tracking the number of iterations attempted.
+/// * `limit` - Iteration and gap limits for the search.
+/// * `cancel` - Checked every few thousand iterations; stops the search once cancelled.
+/// * `progress` - Receives an update every [`PROGRESS_INTERVAL`] iterations and the factors if found.
+///
+/// # Returns
+/// [`FermatOutcome::Found`] with `p ≤ q`, [`FermatOutcome::Exhausted`] with the
+/// [`Searched`] range if the limit stopped the search (`iteration` is then the first
+/// iteration not run), [`FermatOutcome::Cancelled`] likewise if `cancel` stopped it, or
+/// [`FermatOutcome::NoFactor`]. The range only bounds `q − p` if the search started at
+/// iteration 1.
+pub fn bounded_difference_of_squares(
+    n: &Integer,
+    iteration: &mut Integer,
+    limit: &SearchLimit,
+    cancel: &CancelToken,
+    progress: &dyn ProgressSink,
+) -> FermatOutcome {
+    if is_probable_prime(n) {
+        return FermatOutcome::NoFactor;
+    }
+
+    let started = Instant::now();
     let mut a: Integer = sqrt_ceil(n);
-    let print_interval: Integer = Integer::const_from_unsigned(1_000_000);
 
     if *iteration > Integer::ONE {
         a += &*iteration - Integer::ONE;
@@ -61,52 +221,474 @@ pub fn difference_of_squares(
         *iteration = Integer::ONE;
     }
 
-    let mut x2: Integer = a.clone().square() - n;
-    let mut _2a: Integer = Integer::TWO * &a;
+    let len = u64::try_from(&(limit.end(n, &a, iteration) - &a)).unwrap_or(u64::MAX);
+    let first = iteration.clone();
+    let mut next_update = 0u64;
+    let mut stopped = None;
 
-    while &a < n {
-        let should_print = &*iteration % &print_interval == Integer::ONE;
-        let is_perf_sqr = if should_print {
-            true
+    let a_first = a.clone();
+    let mut batch = |offset: u64| {
+        if cancel.is_cancelled() {
+            stopped = Some(offset);
+            return false;
+        }
+        if offset >= next_update {
+            next_update = offset + PROGRESS_INTERVAL;
+            let iteration = &first + Integer::from(offset);
+            let a = &a_first + Integer::from(offset);
+            report(progress, n, &iteration, &a, started);
+        }
+        true
+    };
+    let hit = match (u64::try_from(n), u64::try_from(&a)) {
+        (Ok(n), Ok(a)) => native::scan(n, None, a, len, |offset, _, _| batch(offset))
+            .map(|(offset, a, x)| (offset, Integer::from(a), Integer::from(x))),
+        _ => scan_auto(n, &Wheel::new(n, WHEEL_PRIMES), a.clone(), len, batch),
+    };
+    let Some((offset, a_hit, x)) = hit else {
+        if let Some(offset) = stopped {
+            return cancelled(n, iteration, &a, &a, offset);
+        }
+        *iteration += Integer::from(len);
+        let end = &a + Integer::from(len);
+        return if &end < n {
+            FermatOutcome::Exhausted {
+                searched: Searched::new(n, a, end),
+            }
         } else {
-            is_probably_square(&x2)
+            FermatOutcome::NoFactor
         };
+    };
+    *iteration += Integer::from(offset);
 
-        let (is_exact_sqrt, x) = if is_perf_sqr {
-            sqrt_exact(&x2)
-        } else {
-            (false, x2.clone())
-        };
+    finish(n, iteration, &a_hit, &x, progress, started)
+}
+
+/// Iterations between [`ProgressSink::update`] calls of [`bounded_difference_of_squares`].
+pub const PROGRESS_INTERVAL: u64 = 1_000_000;
+
+/// Sends the search state at `a` to `progress`, unless `a` itself splits `n`: the search
+/// is about to pass that candidate to [`ProgressSink::found`].
+fn report(
+    progress: &dyn ProgressSink,
+    n: &Integer,
+    iteration: &Integer,
+    a: &Integer,
+    started: Instant,
+) {
+    let (is_square, x) = sqrt_exact(&(a.square() - n));
+    if is_square {
+        return;
+    }
+    let (p, q) = factor(a, &x, Integer::ONE, Integer::ONE);
+    progress.update(&Progress {
+        iteration,
+        a,
+        p: &p,
+        q: &q,
+        elapsed: started.elapsed(),
+    });
+}
+
+/// Turns a hit `a² − n = x²` into the outcome, passing the factors to `progress`.
+pub(crate) fn finish(
+    n: &Integer,
+    iteration: &Integer,
+    a: &Integer,
+    x: &Integer,
+    progress: &dyn ProgressSink,
+    started: Instant,
+) -> FermatOutcome {
+    let (p, q) = factor(a, x, Integer::ONE, Integer::ONE);
+
+    if p == Integer::ONE || q == Integer::ONE || &p == n || &q == n {
+        return FermatOutcome::NoFactor;
+    }
 
-        if is_exact_sqrt {
-            let (p, q) = factor(&a, &x, Integer::ONE, Integer::ONE);
+    progress.found(&Progress {
+        iteration,
+        a,
+        p: &p,
+        q: &q,
+        elapsed: started.elapsed(),
+    });
 
-            if p == Integer::ONE || q == Integer::ONE || &p == n || &q == n {
+    FermatOutcome::Found(p, q)
+}
+
+/// Number of consecutive `a` values each worker of [`parallel_difference_of_squares`] scans.
+pub const PARALLEL_CHUNK: u64 = 1 << 16;
+
+/// Widest [`Fixed`] type, in limbs, that the Fermat loop uses before falling back to
+/// malachite `Integer`s.
+pub const FIXED_MAX_LIMBS: u64 = 34;
+
+/// Steps between updates of the bigint `a` and `a² − n`, which are also the points where
+/// the caller of [`scan`] can report progress or stop the search.
+const BATCH_STEPS: u64 = 1024;
+
+/// The core Fermat loop: scans the admissible `a` values in `[a0, a0 + len)` and returns
+/// the first hit as `(offset, a, x)` with `a² − n = x²`.
+///
+/// Candidates come from the residue [`Wheel`] and are screened by a [`SquareFilter`] that
+/// steps along with them, so most of them never touch a big number. The full `a` and
+/// `a² − n`, held as `T`, are only brought up to date every [`BATCH_STEPS`] steps and for
+/// candidates that pass the filter. `batch` is called with the current offset at each
+/// update; returning `false` stops the search.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `wheel` - The residue wheel for `n`.
+/// * `a0` - The first `a` to test, at least `⌈√n⌉`.
+/// * `len` - The number of `a` values to scan.
+/// * `batch` - Progress and cancellation callback.
+///
+/// # Returns
+/// `Some((offset, a, x))` with `a = a0 + offset` for the first hit, otherwise `None`.
+///
+/// # Panics
+/// If `a0² − n` does not fit in `T`.
+pub fn scan<T, F>(
+    n: &Integer,
+    wheel: &Wheel,
+    a0: Integer,
+    len: u64,
+    mut batch: F,
+) -> Option<(u64, Integer, Integer)>
+where
+    T: FermatInt,
+    F: FnMut(u64) -> bool,
+{
+    let mut filter = SquareFilter::new(n, &a0, WHEEL_PRIMES);
+    let (mut index, mut offset) = wheel.start(&a0);
+    filter.advance(offset);
+    let mut base = 0u64;
+    let too_wide = "a² − n does not fit in the chosen width";
+    let mut x2 = T::from_integer(&(a0.clone().square() - n)).expect(too_wide);
+    let mut a = T::from_integer(&a0).expect(too_wide);
+    let mut steps = 0u64;
+
+    while offset < len {
+        if steps.is_multiple_of(BATCH_STEPS) {
+            T::shift(&mut a, &mut x2, offset - base);
+            base = offset;
+            if !batch(offset) {
                 return None;
             }
+        }
+        if filter.passes() {
+            let (mut a_hit, mut x2_hit) = (a.clone(), x2.clone());
+            T::shift(&mut a_hit, &mut x2_hit, offset - base);
+            if let Some(x) = x2_hit.sqrt_exact() {
+                return Some((offset, a_hit.to_integer(), x.to_integer()));
+            }
+        }
+        let step = wheel.advance(&mut index);
+        filter.advance(step);
+        offset += step;
+        steps += 1;
+    }
+    None
+}
+
+/// Runs [`scan`] with the narrowest [`Fixed`] width that holds every `a` and `a² − n`
+/// of the range, and with malachite `Integer`s beyond [`FIXED_MAX_LIMBS`].
+///
+/// Near `√n`, `a² − n` is about as wide as `a`, so a 4096-bit `n` needs 34 limbs.
+pub(crate) fn scan_auto<F>(
+    n: &Integer,
+    wheel: &Wheel,
+    a0: Integer,
+    len: u64,
+    batch: F,
+) -> Option<(u64, Integer, Integer)>
+where
+    F: FnMut(u64) -> bool,
+{
+    let a_end = &a0 + Integer::from(len);
+    let x2_end = (&a_end).square() - n;
+    let limbs = a_end
+        .significant_bits()
+        .max(x2_end.significant_bits())
+        .div_ceil(64);
+    match limbs {
+        0..=3 => scan::<Fixed<3>, F>(n, wheel, a0, len, batch),
+        4 => scan::<Fixed<4>, F>(n, wheel, a0, len, batch),
+        5..=6 => scan::<Fixed<6>, F>(n, wheel, a0, len, batch),
+        7..=10 => scan::<Fixed<10>, F>(n, wheel, a0, len, batch),
+        11..=18 => scan::<Fixed<18>, F>(n, wheel, a0, len, batch),
+        19..=FIXED_MAX_LIMBS => scan::<Fixed<34>, F>(n, wheel, a0, len, batch),
+        _ => scan::<Integer, F>(n, wheel, a0, len, batch),
+    }
+}
 
-            if !quiet {
-                println!();
-                verbose(iteration, &p, &q, prec);
-                println!();
+/// Runs Fermat’s Difference of Squares method on all threads of the current rayon pool.
+///
+/// The `a` range is cut into consecutive chunks of [`PARALLEL_CHUNK`] values, and each
+/// round hands one chunk to every thread. A hit stops all chunks after it, while the
+/// chunks before it run to completion, so the smallest `a` always wins. The result and
+/// the final `iteration` are therefore the same as with [`difference_of_squares`].
+///
+/// Inputs below `2^64` first get a serial [`native::scan`] over [`native::PLAIN_SPAN`]
+/// values, and their chunks run on machine words.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `iteration` - A mutable counter tracking the number of iterations attempted.
+/// * `prec` - Precision used when printing intermediate values in scientific notation (if not in quiet mode).
+/// * `quiet` - If `true`, suppresses all prompts and intermediate output.
+///
+/// # Returns
+/// `Some((p, q))` if a non-trivial factor pair is found, otherwise `None`.
+pub fn parallel_difference_of_squares(
+    n: &Integer,
+    iteration: &mut Integer,
+    prec: u64,
+    quiet: bool,
+) -> Option<(Integer, Integer)> {
+    let limit = SearchLimit::default();
+    let progress = progress::terminal(prec, quiet);
+    bounded_parallel_difference_of_squares(n, iteration, &limit, &CancelToken::new(), &*progress)
+        .found()
+}
+
+/// [`parallel_difference_of_squares`] with a [`SearchLimit`], a [`CancelToken`] and a
+/// [`ProgressSink`].
+///
+/// # Returns
+/// The same outcome and final `iteration` as [`bounded_difference_of_squares`]. When
+/// cancelled, the reported progress is where the lowest unfinished chunk stopped.
+/// `progress` is updated after every round of chunks.
+pub fn bounded_parallel_difference_of_squares(
+    n: &Integer,
+    iteration: &mut Integer,
+    limit: &SearchLimit,
+    cancel: &CancelToken,
+    progress: &dyn ProgressSink,
+) -> FermatOutcome {
+    if is_probable_prime(n) {
+        return FermatOutcome::NoFactor;
+    }
+    let started = Instant::now();
+    if *iteration < Integer::ONE {
+        *iteration = Integer::ONE;
+    }
+
+    let chunks = rayon::current_num_threads().max(1) as u64;
+    let round = Integer::from(chunks * PARALLEL_CHUNK);
+    let first: Integer = sqrt_ceil(n) + (&*iteration - Integer::ONE);
+    let mut start = first.clone();
+    let end = limit.end(n, &start, iteration);
+    let native_n = u64::try_from(n).ok();
+    let remaining = |a0: &Integer| u64::try_from(&(&end - a0)).unwrap_or(u64::MAX);
+
+    // Small inputs are usually done before a wheel would pay off, so they get a serial
+    // plain scan first.
+    if let (Some(n64), Ok(a0)) = (native_n, u64::try_from(&start)) {
+        let len = native::PLAIN_SPAN.min(remaining(&start));
+        let mut stopped = None;
+        let hit = native::scan(n64, None, a0, len, |offset, _, _| {
+            let cancelled = cancel.is_cancelled();
+            if cancelled {
+                stopped = Some(offset);
             }
+            !cancelled
+        });
+        if let Some(offset) = stopped {
+            return cancelled(n, iteration, &first, &start, offset);
+        }
+        if let Some((offset, a, x)) = hit {
+            *iteration += Integer::from(offset);
+            return finish(
+                n,
+                iteration,
+                &Integer::from(a),
+                &Integer::from(x),
+                progress,
+                started,
+            );
+        }
+        *iteration += Integer::from(len);
+        start += Integer::from(len);
+    }
+
+    let wheel = Wheel::new(n, WHEEL_PRIMES);
+    while start < end {
+        // Lowest chunk index with a hit so far
+        let found = AtomicU64::new(u64::MAX);
+        // Per chunk: `Ok` with the hit, if any, or `Err` with the offset where it was cancelled
+        let hits: Vec<_> = (0..chunks)
+            .into_par_iter()
+            .map(|chunk| {
+                let a0 = &start + Integer::from(chunk * PARALLEL_CHUNK);
+                let len = PARALLEL_CHUNK.min(remaining(&a0));
+                let mut stopped = None;
+                let mut go_on = |offset: u64| {
+                    if found.load(Ordering::Relaxed) <= chunk {
+                        return false;
+                    }
+                    let cancelled = cancel.is_cancelled();
+                    if cancelled {
+                        stopped = Some(offset);
+                    }
+                    !cancelled
+                };
+                let hit = match (native_n, u64::try_from(&a0)) {
+                    (Some(n64), Ok(a0)) => {
+                        native::scan(n64, Some(&wheel), a0, len, |step, _, _| go_on(step))
+                            .map(|(step, a, x)| (step, Integer::from(a), Integer::from(x)))
+                    }
+                    _ => scan_auto(n, &wheel, a0, len, go_on),
+                };
+                if let Some(step) = stopped {
+                    return Err(chunk * PARALLEL_CHUNK + step);
+                }
+                let Some((step, a, x)) = hit else {
+                    return Ok(None);
+                };
+                found.fetch_min(chunk, Ordering::Relaxed);
+                Ok(Some((chunk * PARALLEL_CHUNK + step, a, x)))
+            })
+            .collect();
 
-            return Some((p, q));
+        // Chunks come back in order, so the first hit has the smallest `a`, and the first
+        // cancelled chunk bounds how far the search got.
+        for hit in hits {
+            match hit {
+                Ok(Some((offset, a, x))) => {
+                    *iteration += Integer::from(offset);
+                    return finish(n, iteration, &a, &x, progress, started);
+                }
+                Err(offset) => return cancelled(n, iteration, &first, &start, offset),
+                Ok(None) => {}
+            }
         }
 
-        if should_print && !quiet {
-            let (p, q) = factor(&a, &x, Integer::ONE, Integer::ONE);
-            verbose(iteration, &p, &q, prec);
-            print!("\r");
-            io::stdout().flush().unwrap();
+        let step = (&end - &start).min(round.clone());
+        *iteration += &step;
+        start += step;
+        report(progress, n, iteration, &start, started);
+    }
+
+    if &end < n {
+        FermatOutcome::Exhausted {
+            searched: Searched::new(n, first, end),
+        }
+    } else {
+        FermatOutcome::NoFactor
+    }
+}
+
+/// Computes the complete prime factorization of `n` using Fermat splits.
+///
+/// # Arguments
+/// * `n` - The number to factor. Values below `2` have no prime factors.
+/// * `iterations` - Incremented by the total number of Fermat iterations spent across all splits.
+/// * `prec` - Precision used when printing intermediate values in scientific notation (if not in quiet mode).
+/// * `quiet` - If `true`, suppresses all prompts and intermediate output.
+///
+/// # Returns
+/// The prime factors of `n` with their exponents, sorted by prime. Fermat always splits
+/// the odd composites left after trial division, so the factorization is complete.
+pub fn factorize(
+    n: &Integer,
+    iterations: &mut Integer,
+    prec: u64,
+    quiet: bool,
+) -> Vec<(Integer, u64)> {
+    let mut unfactored = Vec::new();
+    let factors = factorize_with(n, iterations, &mut unfactored, |m, iteration| {
+        difference_of_squares(m, iteration, prec, quiet)
+    });
+    debug_assert!(
+        unfactored.is_empty(),
+        "Fermat left {:?} unsplit",
+        unfactored
+    );
+    factors
+}
+
+/// Computes the complete prime factorization of `n` with a caller-supplied splitting method.
+///
+/// Small prime factors are removed by trial division first. Every remaining composite
+/// cofactor is passed to `split`, and each part is checked with [`is_probable_prime`]
+/// until only primes are left. `split` receives a fresh iteration counter starting at `0`
+/// for each cofactor; the final count of every successful split is added to `iterations`.
+///
+/// Cofactors of at most [`MAX_BITS`] bits are tried with [`squfof`] on machine words
+/// before falling back to `split`; those splits do not add to `iterations`.
+///
+/// # Arguments
+/// * `n` - The number to factor. Values below `2` have no prime factors.
+/// * `iterations` - Incremented by the total number of iterations of the successful splits.
+/// * `unfactored` - Receives, sorted, the composites that `split` could not break.
+/// * `split` - Splits a composite, starting from the given iteration counter.
+///
+/// # Returns
+/// The prime factors of `n` with their exponents, sorted by prime. The factorization is
+/// complete only if nothing was added to `unfactored`.
+pub fn factorize_with<F>(
+    n: &Integer,
+    iterations: &mut Integer,
+    unfactored: &mut Vec<Integer>,
+    mut split: F,
+) -> Vec<(Integer, u64)>
+where
+    F: FnMut(&Integer, &mut Integer) -> Option<(Integer, Integer)>,
+{
+    let mut primes: Vec<Integer> = Vec::new();
+    if *n < Integer::TWO {
+        return Vec::new();
+    }
+
+    let mut rest = n.clone();
+    for &p in SMALL_PRIMES {
+        let p = Integer::from(p);
+        while &rest % &p == Integer::ZERO {
+            rest /= &p;
+            primes.push(p.clone());
         }
+    }
 
-        a += Integer::ONE;
-        _2a += Integer::ONE;
-        x2 += &_2a;
-        _2a += Integer::ONE;
-        *iteration += Integer::ONE;
+    let mut composites: Vec<Integer> = Vec::new();
+    if rest > Integer::ONE {
+        composites.push(rest);
     }
 
-    None
+    while let Some(m) = composites.pop() {
+        if is_probable_prime(&m) {
+            primes.push(m);
+            continue;
+        }
+
+        if m.significant_bits() <= MAX_BITS as u64 {
+            if let Some(f) = squfof(u64::wrapping_from(&m)) {
+                let f = Integer::from(f);
+                composites.push(&m / &f);
+                composites.push(f);
+                continue;
+            }
+        }
+
+        let mut iteration = Integer::ZERO;
+        match split(&m, &mut iteration) {
+            Some((p, q)) => {
+                *iterations += iteration;
+                composites.push(p);
+                composites.push(q);
+            }
+            None => unfactored.push(m),
+        }
+    }
+
+    unfactored.sort();
+    primes.sort();
+    let mut factors: Vec<(Integer, u64)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, exponent)) if *last == p => *exponent += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
 }
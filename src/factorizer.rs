@@ -0,0 +1,183 @@
+use crate::cancel::CancelToken;
+use crate::factor::{factorize_with, SearchLimit};
+use crate::progress::{ProgressSink, Silent};
+use crate::split::{split, FactorOutcome, Failure, Method};
+use malachite::base::num::basic::traits::{One, Zero};
+use malachite::Integer;
+use rayon::{ThreadPool, ThreadPoolBuildError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A configured factoring run: the one entry point meant to stay stable across versions.
+///
+/// Starts from Fermat at iteration 1 with no limits, the current rayon pool, a token
+/// that is never cancelled and no progress output. Each setter replaces one setting.
+#[derive(Clone)]
+pub struct Factorizer<'a> {
+    method: Method,
+    start: Integer,
+    limit: SearchLimit,
+    /// The dedicated pool set by [`threads`](Factorizer::threads), shared by clones.
+    pool: Option<Arc<ThreadPool>>,
+    cancel: CancelToken,
+    timeout: Option<Duration>,
+    progress: &'a (dyn ProgressSink + Sync),
+}
+
+/// The result of [`Factorizer::factorize`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Factorization {
+    /// Prime factors with their exponents, sorted by prime.
+    pub factors: Vec<(Integer, u64)>,
+    /// Composite cofactors the method could not split, sorted. The factorization is
+    /// complete when this is empty.
+    pub unfactored: Vec<Integer>,
+    /// Total iterations of the successful splits.
+    pub iterations: Integer,
+    /// Wall time of the run.
+    pub elapsed: Duration,
+    /// Whether cancellation or the timeout cut the run short, leaving cofactors in
+    /// `unfactored`.
+    pub cancelled: bool,
+}
+
+impl Default for Factorizer<'_> {
+    fn default() -> Self {
+        Factorizer {
+            method: Method::Fermat,
+            start: Integer::ONE,
+            limit: SearchLimit::default(),
+            pool: None,
+            cancel: CancelToken::new(),
+            timeout: None,
+            progress: &Silent,
+        }
+    }
+}
+
+impl<'a> Factorizer<'a> {
+    /// The default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The splitting method and its parameters.
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// First Fermat iteration of [`split`](Factorizer::split), to resume a search.
+    /// Ignored by other methods and by [`factorize`](Factorizer::factorize).
+    pub fn start(mut self, iteration: Integer) -> Self {
+        self.start = iteration;
+        self
+    }
+
+    /// Iteration and gap limits for the Fermat search.
+    pub fn limit(mut self, limit: SearchLimit) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Runs on a dedicated pool of `threads` threads instead of the current rayon pool.
+    /// The pool is built here, once, and shared by every clone of the factorizer.
+    ///
+    /// # Returns
+    /// The factorizer, or the error if rayon could not start the threads.
+    pub fn threads(mut self, threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()?;
+        self.pool = Some(Arc::new(pool));
+        Ok(self)
+    }
+
+    /// A token the caller can cancel from another thread.
+    pub fn cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Stops each run `timeout` after it starts.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Where Fermat progress goes. The sink must be `Sync` so the run can move to a
+    /// dedicated pool.
+    pub fn progress(mut self, progress: &'a (dyn ProgressSink + Sync)) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Splits `n` once.
+    ///
+    /// # Returns
+    /// The factors or the reason there are none, with the iteration count, the elapsed
+    /// time and the method, as in [`split`].
+    pub fn split(&self, n: &Integer) -> FactorOutcome {
+        let cancel = self.token();
+        self.install(|| {
+            split(
+                n,
+                self.method,
+                &self.start,
+                &self.limit,
+                &cancel,
+                self.progress,
+            )
+        })
+    }
+
+    /// Computes the complete prime factorization of `n`, as in
+    /// [`factorize_with`] with the configured method for every split.
+    pub fn factorize(&self, n: &Integer) -> Factorization {
+        let started = Instant::now();
+        let cancel = self.token();
+        let mut iterations = Integer::ZERO;
+        let mut unfactored = Vec::new();
+        let mut cancelled = false;
+        let factors = self.install(|| {
+            factorize_with(n, &mut iterations, &mut unfactored, |m, iteration| {
+                let outcome = split(
+                    m,
+                    self.method,
+                    iteration,
+                    &self.limit,
+                    &cancel,
+                    self.progress,
+                );
+                *iteration = outcome.iterations.clone();
+                if let Err(Failure::Cancelled { .. } | Failure::TimedOut { .. }) = outcome.result {
+                    cancelled = true;
+                }
+                outcome.found()
+            })
+        });
+        Factorization {
+            factors,
+            unfactored,
+            iterations,
+            elapsed: started.elapsed(),
+            cancelled,
+        }
+    }
+
+    /// The token for one run, with the timeout counted from now.
+    fn token(&self) -> CancelToken {
+        match self.timeout {
+            Some(timeout) => self.cancel.limited(timeout),
+            None => self.cancel.clone(),
+        }
+    }
+
+    /// Runs `f` on the configured thread pool.
+    fn install<T: Send>(&self, f: impl FnOnce() -> T + Send) -> T {
+        match &self.pool {
+            Some(pool) => pool.install(f),
+            None => f(),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::factor::{finish, scan_auto, FermatOutcome, Searched};
+use crate::native;
+use crate::prime::is_probable_prime;
+use crate::progress::Silent;
+use crate::sqrt::sqrt_ceil;
+use crate::wheel::{Wheel, WHEEL_PRIMES};
+use malachite::base::num::arithmetic::traits::Square;
+use malachite::base::num::basic::traits::One;
+use malachite::Integer;
+use std::time::Instant;
+
+/// Where a [`FermatSearch`] stands: enough to pause it and pick it up later.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FermatState {
+    /// The number being factored.
+    pub n: Integer,
+    /// The next `a` to test.
+    pub a: Integer,
+    /// The iteration of `a`, counted as in
+    /// [`difference_of_squares`](crate::factor::difference_of_squares).
+    pub iteration: Integer,
+    /// The first `a` the search tested, `⌈√n⌉` unless it began further on.
+    pub start: Integer,
+}
+
+/// Fermat's difference of squares method as a state machine the caller drives.
+///
+/// Each [`step`](FermatSearch::step) tests a bounded number of `a` values with the same
+/// wheel and filters as [`bounded_difference_of_squares`](crate::factor::bounded_difference_of_squares),
+/// then returns, so many searches can be interleaved, paused with
+/// [`state`](FermatSearch::state) and rebuilt with [`from_state`](FermatSearch::from_state).
+#[derive(Clone, Debug)]
+pub struct FermatSearch {
+    n: Integer,
+    a: Integer,
+    iteration: Integer,
+    start: Integer,
+    wheel: Wheel,
+    prime: bool,
+}
+
+impl FermatSearch {
+    /// Starts a search for `n` at `a = ⌈√n⌉`, iteration 1.
+    pub fn new(n: &Integer) -> Self {
+        let a = sqrt_ceil(n);
+        FermatSearch::from_state(FermatState {
+            n: n.clone(),
+            start: a.clone(),
+            a,
+            iteration: Integer::ONE,
+        })
+    }
+
+    /// Resumes a search from a saved [`FermatState`].
+    ///
+    /// An `a` below `⌈√n⌉` is raised to it, with the iteration moved along, so a state
+    /// never makes the search test values that cannot be a solution. A `start` below
+    /// `⌈√n⌉` or past `a` is clamped to that range.
+    pub fn from_state(state: FermatState) -> Self {
+        let FermatState {
+            n,
+            mut a,
+            mut iteration,
+            start,
+        } = state;
+        let a0 = sqrt_ceil(&n);
+        if a < a0 {
+            iteration += &a0 - &a;
+            a = a0.clone();
+        }
+        let start = start.max(a0).min(a.clone());
+        FermatSearch {
+            start,
+            wheel: Wheel::new(&n, WHEEL_PRIMES),
+            prime: is_probable_prime(&n),
+            n,
+            a,
+            iteration,
+        }
+    }
+
+    /// The current state, to resume from with [`from_state`](FermatSearch::from_state).
+    pub fn state(&self) -> FermatState {
+        FermatState {
+            n: self.n.clone(),
+            a: self.a.clone(),
+            iteration: self.iteration.clone(),
+            start: self.start.clone(),
+        }
+    }
+
+    /// The number being factored.
+    pub fn n(&self) -> &Integer {
+        &self.n
+    }
+
+    /// The next `a` to test.
+    pub fn a(&self) -> &Integer {
+        &self.a
+    }
+
+    /// The iteration of [`a`](FermatSearch::a).
+    pub fn iteration(&self) -> &Integer {
+        &self.iteration
+    }
+
+    /// `a² − n` for the current `a`.
+    pub fn residue(&self) -> Integer {
+        (&self.a).square() - &self.n
+    }
+
+    /// Tests the next `steps` values of `a`.
+    ///
+    /// # Arguments
+    /// * `steps` - The number of `a` values to test.
+    ///
+    /// # Returns
+    /// [`FermatOutcome::Found`] with `p ≤ q`, leaving the search at the `a` that split
+    /// `n`. [`FermatOutcome::Exhausted`] if none of the `steps` values did, with every `a`
+    /// from the start of the search ruled out; the search then stands at the first `a`
+    /// not tested.
+    /// [`FermatOutcome::NoFactor`] once `a` reaches `n`, or for a prime `n`.
+    pub fn step(&mut self, steps: u64) -> FermatOutcome {
+        if self.prime || self.a >= self.n {
+            return FermatOutcome::NoFactor;
+        }
+        let len = u64::try_from(&(&self.n - &self.a)).map_or(steps, |left| left.min(steps));
+        let hit = match (u64::try_from(&self.n), u64::try_from(&self.a)) {
+            (Ok(n), Ok(a)) => native::scan(n, Some(&self.wheel), a, len, |_, _, _| true)
+                .map(|(offset, a, x)| (offset, Integer::from(a), Integer::from(x))),
+            _ => scan_auto(&self.n, &self.wheel, self.a.clone(), len, |_| true),
+        };
+        let Some((offset, a, x)) = hit else {
+            self.a += Integer::from(len);
+            self.iteration += Integer::from(len);
+            return if self.a < self.n {
+                FermatOutcome::Exhausted {
+                    searched: Searched::new(&self.n, self.start.clone(), self.a.clone()),
+                }
+            } else {
+                FermatOutcome::NoFactor
+            };
+        };
+        self.a = a;
+        self.iteration += Integer::from(offset);
+        finish(
+            &self.n,
+            &self.iteration,
+            &self.a,
+            &x,
+            &Silent,
+            Instant::now(),
+        )
+    }
+}
@@ -0,0 +1,151 @@
+use crate::legendre::{LEGENDRE_TABLES, QR_11, QR_63, QR_64, QR_65, QR_ODD_MODULUS};
+use crate::sqrt::sqrt_exact;
+use malachite::base::num::arithmetic::traits::{FloorSqrt, Square};
+use malachite::base::num::basic::traits::Two;
+use malachite::{Integer, Natural};
+
+/// The operations the Fermat loop needs from its `a` and `a² − n` values.
+pub trait FermatInt: Clone {
+    /// Converts from a non-negative `Integer`, or `None` if it does not fit.
+    fn from_integer(x: &Integer) -> Option<Self>;
+    /// Converts back to an `Integer`.
+    fn to_integer(&self) -> Integer;
+    /// Moves `a` to `a + t` and `x2 = a² − n` to `(a + t)² − n`, in place.
+    fn shift(a: &mut Self, x2: &mut Self, t: u64);
+    /// Returns `√x` if `x` is a perfect square.
+    fn sqrt_exact(&self) -> Option<Self>;
+}
+
+impl FermatInt for Integer {
+    fn from_integer(x: &Integer) -> Option<Self> {
+        Some(x.clone())
+    }
+
+    fn to_integer(&self) -> Integer {
+        self.clone()
+    }
+
+    fn shift(a: &mut Self, x2: &mut Self, t: u64) {
+        // (a + t)² − n = a² − n + t·(2a + t)
+        let t = Integer::from(t);
+        *x2 += (Integer::TWO * &*a + &t) * &t;
+        *a += t;
+    }
+
+    fn sqrt_exact(&self) -> Option<Self> {
+        let (is_square, root) = sqrt_exact(self);
+        is_square.then_some(root)
+    }
+}
+
+/// A fixed-width unsigned integer of `LIMBS` 64-bit limbs, least significant first.
+///
+/// Lives entirely on the stack and implements only what the Fermat loop needs, all in
+/// place: adding, adding a multiple, small-modulus reduction and the square check.
+/// Additions wrap modulo `2^(64·LIMBS)`; callers pick `LIMBS` so that nothing overflows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fixed<const LIMBS: usize> {
+    pub limbs: [u64; LIMBS],
+}
+
+impl<const LIMBS: usize> Fixed<LIMBS> {
+    /// Width of the type in bits.
+    pub const BITS: u64 = 64 * LIMBS as u64;
+
+    /// Converts from a `Natural`, or `None` if it needs more than `LIMBS` limbs.
+    pub fn from_natural(x: &Natural) -> Option<Self> {
+        let mut limbs = [0u64; LIMBS];
+        for (i, limb) in x.limbs().enumerate() {
+            *limbs.get_mut(i)? = limb;
+        }
+        Some(Fixed { limbs })
+    }
+
+    /// Converts back to a `Natural`.
+    pub fn to_natural(&self) -> Natural {
+        Natural::from_limbs_asc(&self.limbs)
+    }
+
+    /// Adds `x`, returning the carry out of the top limb.
+    pub fn add_small(&mut self, x: u64) -> bool {
+        let mut carry = x;
+        for limb in self.limbs.iter_mut() {
+            let (sum, overflow) = limb.overflowing_add(carry);
+            *limb = sum;
+            carry = overflow as u64;
+            if carry == 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Adds `x · m`, returning whether the result overflowed.
+    pub fn add_mul_small(&mut self, x: &Self, m: u64) -> bool {
+        let mut carry = 0u64;
+        for (limb, &y) in self.limbs.iter_mut().zip(&x.limbs) {
+            let t = *limb as u128 + y as u128 * m as u128 + carry as u128;
+            *limb = t as u64;
+            carry = (t >> 64) as u64;
+        }
+        carry != 0
+    }
+
+    /// The remainder modulo `m`, for `0 < m < 2^32`.
+    pub fn rem_small(&self, m: u64) -> u64 {
+        debug_assert!(m > 0 && m < 1 << 32);
+        let two_64 = ((1u128 << 64) % m as u128) as u64;
+        self.limbs
+            .iter()
+            .rev()
+            .fold(0, |r, &limb| (r * two_64 + limb % m) % m)
+    }
+
+    /// Fast check whether `self` could be a square, as in
+    /// [`is_probably_square`](crate::sqrt::is_probably_square).
+    pub fn is_probably_square(&self) -> bool {
+        if !QR_64.contains(self.limbs[0]) {
+            return false;
+        }
+        let r = self.rem_small(QR_ODD_MODULUS);
+        if !(QR_63.contains(r) && QR_65.contains(r) && QR_11.contains(r)) {
+            return false;
+        }
+        LEGENDRE_TABLES
+            .iter()
+            .filter(|&&(p, _)| !QR_ODD_MODULUS.is_multiple_of(p))
+            .all(|&(p, table)| table[self.rem_small(p) as usize] != -1)
+    }
+}
+
+impl<const LIMBS: usize> FermatInt for Fixed<LIMBS> {
+    fn from_integer(x: &Integer) -> Option<Self> {
+        if *x < 0 {
+            return None;
+        }
+        Self::from_natural(x.unsigned_abs_ref())
+    }
+
+    fn to_integer(&self) -> Integer {
+        Integer::from(self.to_natural())
+    }
+
+    fn shift(a: &mut Self, x2: &mut Self, t: u64) {
+        // (a + t)² − n = a² − n + t·(a + (a + t))
+        let old = *a;
+        let overflow = a.add_small(t) | x2.add_mul_small(&old, t) | x2.add_mul_small(a, t);
+        debug_assert!(!overflow, "Fermat values overflowed {} bits", Self::BITS);
+    }
+
+    fn sqrt_exact(&self) -> Option<Self> {
+        if !self.is_probably_square() {
+            return None;
+        }
+        let x = self.to_natural();
+        let root = (&x).floor_sqrt();
+        if (&root).square() != x {
+            return None;
+        }
+        Self::from_natural(&root)
+    }
+}
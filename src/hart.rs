@@ -0,0 +1,86 @@
+use crate::cancel::CancelToken;
+use crate::pm1::pair;
+use crate::prime::is_probable_prime;
+use crate::sqrt::{is_probably_square, sqrt_ceil, sqrt_exact};
+use malachite::base::num::arithmetic::traits::{Gcd, Parity, Square, UnsignedAbs};
+use malachite::base::num::basic::traits::{One, Zero};
+use malachite::{Integer, Natural};
+
+/// Rounds between checks of the [`CancelToken`] in [`hart`].
+pub const CANCEL_ROUNDS: u64 = 1024;
+
+/// One step of Hart's one-line factoring for the multiple `ni = n·i`.
+///
+/// Computes `s = ⌈√(n·i)⌉` and `m = s² mod n`. If `m = t²` is a square,
+/// `gcd(s − t, n)` is returned when it is a proper factor.
+pub fn one_line_step(n: &Integer, ni: &Integer) -> Option<Natural> {
+    let s = sqrt_ceil(ni);
+    let m = ((&s).square() - ni) % n;
+    if !is_probably_square(&m) {
+        return None;
+    }
+    let (is_square, t) = sqrt_exact(&m);
+    if !is_square {
+        return None;
+    }
+    let n = n.unsigned_abs_ref();
+    let g = (s - t).unsigned_abs_ref().gcd(n);
+    (g != Natural::ONE && g != *n).then_some(g)
+}
+
+/// Attempts to factor `n` with Hart's one-line factoring algorithm raced against Fermat.
+///
+/// Each round runs one Hart step for the next multiplier `i` and one step of Fermat's
+/// difference of squares. Hart's method finds factors whose ratio `p/q` is close to a
+/// small rational, where Fermat struggles; Fermat keeps the guarantee of finding close
+/// factors, and of terminating for every odd composite. Both squareness checks go through
+/// the [`is_probably_square`] Legendre filter first.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `iteration` - A mutable counter tracking the number of rounds.
+/// * `cancel` - Checked every [`CANCEL_ROUNDS`] rounds; stops the search once cancelled.
+///
+/// # Returns
+/// `Some((p, q))` with `p ≤ q` if a non-trivial factor pair is found, otherwise `None`.
+pub fn hart(
+    n: &Integer,
+    iteration: &mut Integer,
+    cancel: &CancelToken,
+) -> Option<(Integer, Integer)> {
+    if *n < 4 || is_probable_prime(n) {
+        return None;
+    }
+    let n_nat = n.unsigned_abs_ref();
+    if n_nat.even() {
+        return Some(pair(Natural::from(2u32), n_nat));
+    }
+
+    let mut ni = Integer::ZERO;
+    let mut a = sqrt_ceil(n);
+    let mut x2 = (&a).square() - n;
+    for round in 0u64.. {
+        if round.is_multiple_of(CANCEL_ROUNDS) && cancel.is_cancelled() {
+            return None;
+        }
+        *iteration += Integer::ONE;
+
+        ni += n;
+        if let Some(g) = one_line_step(n, &ni) {
+            return Some(pair(g, n_nat));
+        }
+
+        if is_probably_square(&x2) {
+            let (is_square, x) = sqrt_exact(&x2);
+            if is_square {
+                let p = (&a - x).unsigned_abs();
+                if p != Natural::ONE {
+                    return Some(pair(p, n_nat));
+                }
+            }
+        }
+        x2 += Integer::from(2) * &a + Integer::ONE;
+        a += Integer::ONE;
+    }
+    None
+}
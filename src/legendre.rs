@@ -1,3 +1,4 @@
+/// Legendre symbol tables `(p, χ)` with `χ[r] = (r | p)` for the odd primes up to 73.
 pub const LEGENDRE_TABLES: &[(u64, &[i8])] = &[
     (3, &[0, 1, -1]),
     (5, &[0, 1, -1, -1, 1]),
@@ -111,3 +112,60 @@ pub const LEGENDRE_TABLES: &[(u64, &[i8])] = &[
         ],
     ),
 ];
+
+/// A packed table of the quadratic residues modulo `M`: bit `r` is set if and only if
+/// `r ≡ x² (mod M)` for some `x`.
+///
+/// Tables are built at compile time with [`QrTable::new`]. `WORDS` must be at least `⌈M / 64⌉`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QrTable<const WORDS: usize> {
+    pub modulus: u64,
+    pub bits: [u64; WORDS],
+}
+
+impl<const WORDS: usize> QrTable<WORDS> {
+    /// Builds the table for `modulus` by marking `x² mod M` for every `x < M`.
+    pub const fn new(modulus: u64) -> Self {
+        assert!(
+            modulus > 0 && modulus <= 64 * WORDS as u64,
+            "modulus does not fit in the table"
+        );
+        let mut bits = [0u64; WORDS];
+        let mut x = 0;
+        while x < modulus {
+            let r = x * x % modulus;
+            bits[(r / 64) as usize] |= 1 << (r % 64);
+            x += 1;
+        }
+        QrTable { modulus, bits }
+    }
+
+    /// Whether `r mod M` is a quadratic residue (zero included).
+    pub const fn contains(&self, r: u64) -> bool {
+        let r = r % self.modulus;
+        (self.bits[(r / 64) as usize] >> (r % 64)) & 1 == 1
+    }
+
+    /// Number of quadratic residues modulo `M`.
+    pub const fn count(&self) -> u32 {
+        let mut total = 0;
+        let mut i = 0;
+        while i < WORDS {
+            total += self.bits[i].count_ones();
+            i += 1;
+        }
+        total
+    }
+}
+
+/// Squares modulo 64: 12 of 64 residues, read straight from the lowest limb.
+pub const QR_64: QrTable<1> = QrTable::new(64);
+/// Squares modulo 63 = 9 · 7: 16 of 63 residues.
+pub const QR_63: QrTable<1> = QrTable::new(63);
+/// Squares modulo 65 = 5 · 13: 21 of 65 residues.
+pub const QR_65: QrTable<2> = QrTable::new(65);
+/// Squares modulo 11: 6 of 11 residues.
+pub const QR_11: QrTable<1> = QrTable::new(11);
+
+/// `63 · 65 · 11`: one bigint remainder by this feeds all three odd tables.
+pub const QR_ODD_MODULUS: u64 = 63 * 65 * 11;
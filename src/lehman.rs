@@ -0,0 +1,79 @@
+use crate::cancel::CancelToken;
+use crate::hart::CANCEL_ROUNDS;
+use crate::pm1::pair;
+use crate::sqrt::{is_probably_square, sqrt_ceil, sqrt_exact};
+use malachite::base::num::arithmetic::traits::{DivisibleBy, FloorRoot, Gcd, Square};
+use malachite::base::num::basic::traits::One;
+use malachite::base::num::conversion::traits::RoundingFrom;
+use malachite::base::rounding_modes::RoundingMode::Ceiling;
+use malachite::{Integer, Natural};
+
+/// Attempts to factor `n` using Lehman's method.
+///
+/// Trial division runs up to `r = ⌊n^(1/3)⌋`. Then, for every `k = 1, ..., r`, the values
+/// `a` from `⌈√(4kn)⌉` up to `√(4kn) + n^(1/6) / (4√k)` are tested for `a² − 4kn = b²`,
+/// in which case `gcd(a + b, n)` is a proper factor. Unlike plain Fermat, the total work
+/// is bounded by `O(n^(1/3))` steps, so the search always terminates with an answer.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `iteration` - A mutable counter tracking the number of trial divisions and `a` values tested.
+/// * `cancel` - Checked every [`CANCEL_ROUNDS`] trial divisions and every [`CANCEL_ROUNDS`]
+///   values of `k`; stops the search once cancelled.
+///
+/// # Returns
+/// `Some((p, q))` with `p ≤ q` if `n` is composite. `None` for `n < 4`, for every prime
+/// `n` and for a cancelled search, so a `None` from a search that was not cancelled is a
+/// proof of primality.
+pub fn lehman(
+    n: &Integer,
+    iteration: &mut Integer,
+    cancel: &CancelToken,
+) -> Option<(Integer, Integer)> {
+    if *n < 4 {
+        return None;
+    }
+    let n_nat = n.unsigned_abs_ref();
+    let cube_root = u64::try_from(&n_nat.floor_root(3)).unwrap_or(u64::MAX);
+
+    // Trial division up to n^(1/3)
+    for (round, d) in (0u64..).zip(std::iter::once(2).chain((3..=cube_root).step_by(2))) {
+        if round.is_multiple_of(CANCEL_ROUNDS) && cancel.is_cancelled() {
+            return None;
+        }
+        *iteration += Integer::ONE;
+        let d = Natural::from(d);
+        if n_nat.divisible_by(&d) {
+            return Some(pair(d, n_nat));
+        }
+    }
+
+    // Any composite left is a product of two primes above n^(1/3); some k catches it.
+    let sixth_root = f64::rounding_from(&n_nat.floor_root(6), Ceiling).0 + 1.0;
+    for k in 1..=cube_root {
+        if k.is_multiple_of(CANCEL_ROUNDS) && cancel.is_cancelled() {
+            return None;
+        }
+        let four_kn = Integer::from(Natural::from(k) * n_nat) << 2u32;
+        let width = (sixth_root / (4.0 * (k as f64).sqrt())).floor();
+        let start = sqrt_ceil(&four_kn);
+        let end = (&four_kn).floor_root(2) + Integer::from(width as u64) + Integer::ONE;
+
+        let mut a = start;
+        while a <= end {
+            *iteration += Integer::ONE;
+            let b2 = (&a).square() - &four_kn;
+            if is_probably_square(&b2) {
+                let (is_square, b) = sqrt_exact(&b2);
+                if is_square {
+                    let g = (&a + b).unsigned_abs_ref().gcd(n_nat);
+                    if g != Natural::ONE && g != *n_nat {
+                        return Some(pair(g, n_nat));
+                    }
+                }
+            }
+            a += Integer::ONE;
+        }
+    }
+    None
+}
@@ -1,3 +1,23 @@
+pub mod backend;
+pub mod cancel;
+pub mod checkpoint;
+pub mod ecm;
 pub mod factor;
+pub mod factorizer;
+pub mod fermat;
+pub mod fixed;
+pub mod hart;
 pub mod legendre;
+pub mod lehman;
+pub mod native;
+pub mod pm1;
+pub mod pp1;
+pub mod prime;
+pub mod progress;
+pub mod qs;
+pub mod ratio;
+pub mod rho;
+pub mod split;
 pub mod sqrt;
+pub mod squfof;
+pub mod wheel;
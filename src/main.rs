@@ -1,7 +1,17 @@
 use anyhow::{anyhow, Result};
-use atty;
-use clap::{ArgAction, Parser};
-use diffsquare::factor::difference_of_squares;
+use clap::{ArgAction, Parser, ValueEnum};
+use diffsquare::{
+    checkpoint::{Checkpoint, CheckpointSink},
+    ecm::DEFAULT_CURVES,
+    factor::{SearchLimit, Searched},
+    factorizer::{Factorization, Factorizer},
+    pm1::{DEFAULT_B1, DEFAULT_B2},
+    prime::is_probable_prime,
+    progress::{ProgressSink, Silent, Terminal},
+    ratio::Ratio,
+    split::{self, FactorOutcome, Failure},
+    sqrt::sqrt_ceil,
+};
 use indicatif::{ProgressBar, ProgressStyle};
 use malachite::{
     base::num::conversion::traits::{FromSciString, FromStringBase},
@@ -12,7 +22,7 @@ use serde::Serialize;
 use std::{
     fs::OpenOptions,
     io::{self, Read, Write},
-    thread,
+    path::Path,
     time::{Duration, Instant},
 };
 
@@ -30,7 +40,7 @@ struct Args {
     #[arg(short = 'n', long = "mod", display_order = 1)]
     modulus: Option<String>,
 
-    /// Starting iteration value
+    /// Starting iteration value (Fermat only)
     #[arg(short, long, display_order = 2)]
     iter: Option<String>,
 
@@ -62,7 +72,7 @@ struct Args {
     #[arg(long, display_order = 9)]
     input: Option<String>,
 
-    /// Number of threads for batch factorization (`--stdin` / `--input`) (default: logical CPUs)
+    /// Number of threads for batch inputs and the Fermat search (default: logical CPUs)
     #[arg(long, display_order = 10)]
     threads: Option<usize>,
 
@@ -74,6 +84,58 @@ struct Args {
     #[arg(long, display_order = 12)]
     timeout: Option<u64>,
 
+    /// Find the complete prime factorization instead of a single split
+    #[arg(short, long, display_order = 13)]
+    full: bool,
+
+    /// Factoring method used to split the modulus
+    #[arg(short, long, value_enum, default_value_t = Method::Fermat, display_order = 14)]
+    method: Method,
+
+    /// Starting value for randomized methods (`rho`, `ecm`)
+    #[arg(long, default_value_t = 2, display_order = 15)]
+    seed: u64,
+
+    /// Stage 1 bound for `pm1`, `pp1` and `ecm`
+    #[arg(long, default_value_t = DEFAULT_B1, display_order = 16)]
+    b1: u64,
+
+    /// Stage 2 bound for `pm1` and `ecm`
+    #[arg(long, default_value_t = DEFAULT_B2, display_order = 17)]
+    b2: u64,
+
+    /// Number of curves for `ecm`
+    #[arg(long, default_value_t = DEFAULT_CURVES, display_order = 18)]
+    curves: u64,
+
+    /// Expected factor ratio `u/v` for Fermat, searching on `4·u·v·n`
+    #[arg(long, value_name = "U/V", display_order = 19)]
+    ratio: Option<Ratio>,
+
+    /// Try every factor ratio `u/v` with `u, v ≤ K` side by side (Fermat only)
+    #[arg(long, value_name = "K", conflicts_with = "ratio", display_order = 20)]
+    auto_ratio: Option<u64>,
+
+    /// Last Fermat iteration to run; the search stops after it
+    #[arg(long, value_name = "N", conflicts_with_all = ["ratio", "auto_ratio"], display_order = 21)]
+    max_iter: Option<u64>,
+
+    /// Only search for factors with |p − q| ≤ 2^BITS (Fermat only)
+    #[arg(long, value_name = "BITS", conflicts_with_all = ["ratio", "auto_ratio"], display_order = 22)]
+    max_gap: Option<u64>,
+
+    /// Periodically save the Fermat search position to this file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["stdin", "input", "full"], display_order = 23)]
+    checkpoint: Option<String>,
+
+    /// Time between checkpoints, such as `90s`, `15m` or `2h`
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "60s", display_order = 24)]
+    checkpoint_every: Duration,
+
+    /// Continue the Fermat search saved in a checkpoint file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["modulus", "iter", "stdin", "input", "full"], display_order = 25)]
+    resume: Option<String>,
+
     /// Show usage help
     #[arg(short = 'h', long = "help", action = ArgAction::Help, display_order = 100)]
     help: Option<bool>,
@@ -83,10 +145,77 @@ struct Args {
     version: Option<bool>,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Method {
+    /// Fermat's difference of squares (fast when the factors are close together)
+    Fermat,
+    /// Pollard's rho with Brent's cycle finding (fast when one factor is small)
+    Rho,
+    /// Pollard's p−1 (fast when p − 1 is smooth)
+    Pm1,
+    /// Williams' p+1 (fast when p + 1 is smooth)
+    Pp1,
+    /// Lenstra's elliptic curve method (fast when the smallest factor is small)
+    Ecm,
+    /// Self-initializing quadratic sieve (for balanced factors of 40–100 digit inputs)
+    Qs,
+    /// Hart's one-line factoring raced against Fermat (fast when p/q is near a small fraction)
+    Hart,
+}
+
 impl Args {
     fn is_quiet(&self) -> bool {
         self.quiet || self.json || self.csv || self.time_only
     }
+
+    /// The library method for `--method`, `--ratio` and `--auto-ratio`
+    fn split_method(&self) -> split::Method {
+        match self.method {
+            Method::Fermat => match (self.ratio, self.auto_ratio) {
+                (Some(ratio), _) => split::Method::RatioFermat(ratio),
+                (None, Some(k)) => split::Method::AutoRatioFermat(k),
+                (None, None) => split::Method::Fermat,
+            },
+            Method::Rho => split::Method::Rho { seed: self.seed },
+            Method::Pm1 => split::Method::Pm1 {
+                b1: self.b1,
+                b2: self.b2,
+            },
+            Method::Pp1 => split::Method::Pp1 { b1: self.b1 },
+            Method::Ecm => split::Method::Ecm {
+                b1: self.b1,
+                b2: self.b2,
+                curves: self.curves,
+                seed: self.seed,
+            },
+            Method::Qs => split::Method::Qs,
+            Method::Hart => split::Method::Hart,
+        }
+    }
+
+    /// Where to save checkpoints: `--checkpoint`, or else the `--resume` file itself
+    fn checkpoint_path(&self) -> Option<&Path> {
+        self.checkpoint
+            .as_deref()
+            .or(self.resume.as_deref())
+            .map(Path::new)
+    }
+
+    /// A factorizer for the selected method and limits, reporting to `progress` and
+    /// stopping after `--timeout` if set
+    fn factorizer<'a>(&self, progress: &'a (dyn ProgressSink + Sync)) -> Factorizer<'a> {
+        let factorizer = Factorizer::new()
+            .method(self.split_method())
+            .limit(SearchLimit {
+                max_iterations: self.max_iter.map(Integer::from),
+                max_gap_bits: self.max_gap,
+            })
+            .progress(progress);
+        match self.timeout {
+            Some(ms) => factorizer.timeout(Duration::from_millis(ms)),
+            None => factorizer,
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -114,98 +243,531 @@ fn parse_bigint(s: &str) -> Result<Integer> {
     }
 }
 
+/// Parses a duration such as `500ms`, `90s`, `15m`, `2h` or `1d`; a bare number is seconds
+fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(digits);
+    let value: u64 = value.parse().map_err(|_| anyhow!("Invalid duration"))?;
+    let ms = match unit.trim() {
+        "ms" => 1,
+        "" | "s" => 1000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        unit => return Err(anyhow!("Unknown duration unit '{}'", unit)),
+    };
+    Ok(Duration::from_millis(value.saturating_mul(ms)))
+}
+
+/// The multiplier of `n` that a Fermat method searches on
+fn fermat_multiplier(method: split::Method) -> Integer {
+    match method {
+        split::Method::RatioFermat(ratio) => ratio.multiplier(),
+        _ => Integer::from(1),
+    }
+}
+
+/// How checkpoints name a Fermat method, including the ratio a resumed search must match
+fn checkpoint_method(method: split::Method) -> String {
+    match method {
+        split::Method::RatioFermat(ratio) => format!("{} {}", method.name(), ratio),
+        _ => method.name().to_string(),
+    }
+}
+
 fn write_output(file: &str, content: &str) -> Result<()> {
     let mut f = OpenOptions::new().create(true).append(true).open(file)?;
     writeln!(f, "{}", content)?;
     Ok(())
 }
 
-trait JoinTimeout<T> {
-    fn join_timeout(self, dur: Duration) -> Option<T>;
+#[derive(Serialize)]
+struct JsonFactor {
+    prime: String,
+    exponent: u64,
 }
 
-impl<T: Send + 'static> JoinTimeout<T> for thread::JoinHandle<T> {
-    fn join_timeout(self, dur: Duration) -> Option<T> {
-        use std::sync::mpsc::channel;
-        let (tx, rx) = channel();
-        thread::spawn(move || {
-            if let Ok(val) = self.join() {
-                let _ = tx.send(val);
-            }
-        });
-        rx.recv_timeout(dur).ok()
+#[derive(Serialize)]
+struct JsonFactorization {
+    modulus: String,
+    factors: Vec<JsonFactor>,
+    /// Composite cofactors the method could not split
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unfactored: Vec<String>,
+    iterations: String,
+    time_ms: u128,
+}
+
+#[derive(Serialize)]
+struct JsonPrime {
+    modulus: String,
+    prime: bool,
+    time_ms: u128,
+}
+
+fn print_prime(
+    n: &Integer,
+    duration: Duration,
+    args: &Args,
+    write_if_needed: &dyn Fn(&str) -> Result<()>,
+) -> Result<()> {
+    let out = if args.csv {
+        format!("{},PRIME,PRIME,0,{}", n, duration.as_millis())
+    } else if args.json {
+        let result = JsonPrime {
+            modulus: n.to_string(),
+            prime: true,
+            time_ms: duration.as_millis(),
+        };
+        serde_json::to_string_pretty(&result)?
+    } else if args.time_only {
+        duration.as_millis().to_string()
+    } else if args.quiet {
+        format!("{} is prime", n)
+    } else {
+        format!("\n🔢 {} is prime.\n⏱️  Execution time: {:?}", n, duration)
+    };
+    println!("{}", &out);
+    write_if_needed(&out)?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct JsonExhausted {
+    modulus: String,
+    exhausted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_gap: Option<String>,
+    searched_from: String,
+    searched_to: String,
+    next_iter: String,
+    time_ms: u128,
+}
+
+/// What a stopped Fermat search ruled out: the bound on |p − q| if it covered every `a`
+/// from ⌈√n⌉, otherwise just the range of `a`
+fn describe_searched(searched: &Searched) -> String {
+    match &searched.min_gap {
+        Some(min_gap) => format!("Any factors p, q satisfy |p − q| ≥ {}", min_gap),
+        None => format!(
+            "Searched a ∈ [{}, {}); earlier a values were not tested",
+            searched.start, searched.end
+        ),
+    }
+}
+
+fn print_exhausted(
+    n: &Integer,
+    searched: &Searched,
+    next: &Integer,
+    duration: Duration,
+    args: &Args,
+    write_if_needed: &dyn Fn(&str) -> Result<()>,
+) -> Result<()> {
+    let out = if args.csv {
+        format!(
+            "{},EXHAUSTED,{},{},{}",
+            n,
+            searched
+                .min_gap
+                .as_ref()
+                .map(Integer::to_string)
+                .unwrap_or_default(),
+            next,
+            duration.as_millis()
+        )
+    } else if args.json {
+        let result = JsonExhausted {
+            modulus: n.to_string(),
+            exhausted: true,
+            min_gap: searched.min_gap.as_ref().map(Integer::to_string),
+            searched_from: searched.start.to_string(),
+            searched_to: searched.end.to_string(),
+            next_iter: next.to_string(),
+            time_ms: duration.as_millis(),
+        };
+        serde_json::to_string_pretty(&result)?
+    } else if args.time_only {
+        duration.as_millis().to_string()
+    } else if args.quiet {
+        match &searched.min_gap {
+            Some(min_gap) => format!("{} exhausted: |p - q| >= {}", n, min_gap),
+            None => format!(
+                "{} exhausted: searched a in [{}, {})",
+                n, searched.start, searched.end
+            ),
+        }
+    } else {
+        format!(
+            "\n⛔ Search limit reached for {}.\n\n{}\n➡️  Resume with --iter {}\n⏱️  Execution time: {:?}",
+            n,
+            describe_searched(searched),
+            next,
+            duration
+        )
+    };
+    println!("{}", &out);
+    write_if_needed(&out)?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct JsonTimeout {
+    modulus: String,
+    timed_out: bool,
+    method: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_gap: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    searched_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    searched_to: Option<String>,
+    iterations: String,
+    time_ms: u128,
+}
+
+/// Reports a search stopped by `--timeout`. For Fermat, `iterations` is also the
+/// `--iter` value that resumes the search.
+fn print_timeout(
+    n: &Integer,
+    method: split::Method,
+    searched: Option<&Searched>,
+    iterations: &Integer,
+    duration: Duration,
+    args: &Args,
+    write_if_needed: &dyn Fn(&str) -> Result<()>,
+) -> Result<()> {
+    let out = if args.csv {
+        let gap = searched
+            .and_then(|searched| searched.min_gap.as_ref())
+            .map(Integer::to_string)
+            .unwrap_or_default();
+        format!(
+            "{},TIMEOUT,{},{},{}",
+            n,
+            gap,
+            iterations,
+            duration.as_millis()
+        )
+    } else if args.json {
+        let result = JsonTimeout {
+            modulus: n.to_string(),
+            timed_out: true,
+            method: method.name(),
+            min_gap: searched
+                .and_then(|searched| searched.min_gap.as_ref())
+                .map(Integer::to_string),
+            searched_from: searched.map(|searched| searched.start.to_string()),
+            searched_to: searched.map(|searched| searched.end.to_string()),
+            iterations: iterations.to_string(),
+            time_ms: duration.as_millis(),
+        };
+        serde_json::to_string_pretty(&result)?
+    } else if args.time_only {
+        duration.as_millis().to_string()
+    } else if args.quiet {
+        format!("{} timed out after {} iterations", n, iterations)
+    } else if let Some(searched) = searched {
+        format!(
+            "\n⌛ Timed out on {}.\n\n{}\n➡️  Resume with --iter {}\n⏱️  Execution time: {:?}",
+            n,
+            describe_searched(searched),
+            iterations,
+            duration
+        )
+    } else {
+        format!(
+            "\n⌛ Timed out on {} after {} iterations.\n⏱️  Execution time: {:?}",
+            n, iterations, duration
+        )
+    };
+    eprintln!("{}", &out);
+    write_if_needed(&out)?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct JsonError {
+    modulus: String,
+    error: &'static str,
+    message: String,
+    method: &'static str,
+    iterations: String,
+    time_ms: u128,
+}
+
+/// Reports a run that ended without a split, with its reason
+fn print_failure(
+    n: &Integer,
+    failure: &Failure,
+    method: split::Method,
+    iterations: &Integer,
+    duration: Duration,
+    args: &Args,
+    write_if_needed: &dyn Fn(&str) -> Result<()>,
+) -> Result<()> {
+    match failure {
+        Failure::Prime => return print_prime(n, duration, args, write_if_needed),
+        Failure::Exhausted { searched } => {
+            return print_exhausted(n, searched, iterations, duration, args, write_if_needed)
+        }
+        Failure::Cancelled { searched } | Failure::TimedOut { searched } => {
+            return print_timeout(
+                n,
+                method,
+                searched.as_ref(),
+                iterations,
+                duration,
+                args,
+                write_if_needed,
+            )
+        }
+        _ => {}
+    }
+
+    let err = if args.csv {
+        format!(
+            "{},ERROR,{},{},{}",
+            n,
+            failure.code(),
+            iterations,
+            duration.as_millis()
+        )
+    } else if args.json {
+        let result = JsonError {
+            modulus: n.to_string(),
+            error: failure.code(),
+            message: failure.to_string(),
+            method: method.name(),
+            iterations: iterations.to_string(),
+            time_ms: duration.as_millis(),
+        };
+        serde_json::to_string_pretty(&result)?
+    } else if args.quiet {
+        format!("{} failed: {}", n, failure)
+    } else {
+        format!(
+            "❌ Failed to factor {} with {}: {}.",
+            n,
+            method.name(),
+            failure
+        )
+    };
+    eprintln!("{}", &err);
+    write_if_needed(&err)?;
+    Ok(())
+}
+
+fn factorize_and_print(
+    n: Integer,
+    start_time: Instant,
+    prec: u64,
+    args: &Args,
+    write_if_needed: &dyn Fn(&str) -> Result<()>,
+) -> Result<()> {
+    let terminal = Terminal { prec };
+    let progress: &(dyn ProgressSink + Sync) = if args.is_quiet() { &Silent } else { &terminal };
+    let Factorization {
+        factors,
+        unfactored,
+        iterations,
+        cancelled,
+        ..
+    } = args.factorizer(progress).factorize(&n);
+
+    let duration = start_time.elapsed();
+
+    // Cofactors left after a timeout are not known to be prime
+    if cancelled {
+        return print_timeout(
+            &n,
+            args.split_method(),
+            None,
+            &iterations,
+            duration,
+            args,
+            write_if_needed,
+        );
     }
+
+    let powers: Vec<String> = factors
+        .iter()
+        .map(|(p, e)| {
+            if *e == 1 {
+                p.to_string()
+            } else {
+                format!("{}^{}", p, e)
+            }
+        })
+        .collect();
+    let composites: Vec<String> = unfactored.iter().map(Integer::to_string).collect();
+
+    let out = if args.csv && !composites.is_empty() {
+        format!(
+            "{},INCOMPLETE,{},{},{},{}",
+            n,
+            powers.join("*"),
+            composites.join("*"),
+            iterations,
+            duration.as_millis()
+        )
+    } else if args.csv {
+        format!(
+            "{},{},{},{}",
+            n,
+            powers.join("*"),
+            iterations,
+            duration.as_millis()
+        )
+    } else if args.json {
+        let result = JsonFactorization {
+            modulus: n.to_string(),
+            factors: factors
+                .iter()
+                .map(|(p, e)| JsonFactor {
+                    prime: p.to_string(),
+                    exponent: *e,
+                })
+                .collect(),
+            unfactored: composites,
+            iterations: iterations.to_string(),
+            time_ms: duration.as_millis(),
+        };
+        serde_json::to_string_pretty(&result)?
+    } else if args.time_only {
+        duration.as_millis().to_string()
+    } else if args.quiet && !composites.is_empty() {
+        let mut parts = powers.clone();
+        parts.push(format!("(not factored: {})", composites.join(" ")));
+        parts.join(" ")
+    } else if args.quiet {
+        powers.join(" ")
+    } else if !composites.is_empty() {
+        format!(
+            "\n⚠️  Incomplete factorization of {} with {}:\n\nPrimes: {}\nNot factored: {}\n⏱️  Execution time: {:?}",
+            n,
+            args.split_method().name(),
+            if powers.is_empty() { "none".to_string() } else { powers.join(" × ") },
+            composites.join(" × "),
+            duration
+        )
+    } else {
+        format!(
+            "\n✅ Prime factorization of {}:\n\n{}\n⏱️  Execution time: {:?}",
+            n,
+            powers.join(" × "),
+            duration
+        )
+    };
+    println!("{}", &out);
+    write_if_needed(&out)?;
+    Ok(())
 }
 
 fn factor_and_print(
     n: Integer,
     iter: Integer,
+    first: &Integer,
     prec: u64,
     args: &Args,
     write_if_needed: &dyn Fn(&str) -> Result<()>,
 ) -> Result<()> {
     let start_time = Instant::now();
-    let quiet = args.is_quiet();
-    let mut iter_clone = iter.clone();
-
-    let result = if let Some(ms) = args.timeout {
-        let n_clone = n.clone();
-        let handle = thread::spawn(move || {
-            difference_of_squares(&n_clone, &mut iter_clone, prec, quiet)
-                .map(|(p, q)| (p, q, iter_clone))
-        });
-        handle.join_timeout(Duration::from_millis(ms)).flatten()
+    // Iteration 0 runs from iteration 1, like every iteration below it
+    let first = &first.clone().max(Integer::from(1));
+
+    if is_probable_prime(&n) {
+        return print_prime(&n, start_time.elapsed(), args, write_if_needed);
+    }
+
+    if args.full {
+        return factorize_and_print(n, start_time, prec, args, write_if_needed);
+    }
+
+    let terminal = Terminal { prec };
+    let mut progress: &(dyn ProgressSink + Sync) =
+        if args.is_quiet() { &Silent } else { &terminal };
+    let label = checkpoint_method(args.split_method());
+    let checkpoints = args
+        .checkpoint_path()
+        .map(|path| CheckpointSink::new(path, args.checkpoint_every, &n, first, &label, progress));
+    if let Some(ref sink) = checkpoints {
+        progress = sink;
+    }
+    let FactorOutcome {
+        result,
+        method,
+        iterations,
+        ..
+    } = args.factorizer(progress).start(iter.clone()).split(&n);
+
+    // A resumed search has also ruled out the `a` values of the run it continues
+    let result = if *first < iter {
+        let start = sqrt_ceil(&n) + first - Integer::from(1);
+        result.map_err(|failure| failure.resumed_from(&n, &start))
     } else {
-        difference_of_squares(&n, &mut iter_clone, prec, quiet).map(|(p, q)| (p, q, iter_clone))
+        result
     };
 
     let duration = start_time.elapsed();
 
-    if let Some((p, q, iterations)) = result {
-        if args.csv {
-            let out = format!("{},{},{},{},{}", n, p, q, iterations, duration.as_millis());
-            println!("{}", &out);
-            write_if_needed(&out)?;
-        } else if args.json {
-            let result = JsonResult {
-                modulus: n.to_string(),
-                factor_1: p.to_string(),
-                factor_2: q.to_string(),
-                iterations: iterations.to_string(),
-                time_ms: duration.as_millis(),
-            };
-            let out = serde_json::to_string_pretty(&result)?;
-            println!("{}", &out);
-            write_if_needed(&out)?;
-        } else if args.time_only {
-            let out = duration.as_millis().to_string();
-            println!("{}", &out);
-            write_if_needed(&out)?;
-        } else if args.quiet {
-            let out = format!("{} {}", p, q);
-            println!("{}", &out);
-            write_if_needed(&out)?;
-        } else {
-            let out = format!(
-                "\n✅ Factors of {}:\n\np = {}\nq = {}\n⏱️  Execution time: {:?}",
-                n, p, q, duration
-            );
-            println!("{}", &out);
-            write_if_needed(&out)?;
+    // A search that stopped early saves where it got to
+    if let (Some(path), Err(failure)) = (args.checkpoint_path(), &result) {
+        if matches!(
+            failure,
+            Failure::Exhausted { .. } | Failure::Cancelled { .. } | Failure::TimedOut { .. }
+        ) {
+            let a = sqrt_ceil(&(&n * fermat_multiplier(method))) + &iterations - Integer::from(1);
+            Checkpoint::new(&n, &a, &iterations, first, &label)
+                .save(path)
+                .map_err(|e| anyhow!("Failed to write checkpoint {}: {e}", path.display()))?;
         }
-    } else {
-        let err = if args.csv {
-            format!("{},ERROR,ERROR,ERROR,ERROR", n)
-        } else if args.json {
-            format!(
-                "{{\n  \"modulus\": \"{}\",\n  \"error\": \"Factorization failed or timed out\"\n}}",
-                n
-            )
-        } else {
-            format!("❌ Failed to factor {} (timeout or failure).", n)
-        };
-        eprintln!("{}", &err);
-        write_if_needed(&err)?;
+    }
+
+    match result {
+        Ok((p, q)) => {
+            if args.csv {
+                let out = format!("{},{},{},{},{}", n, p, q, iterations, duration.as_millis());
+                println!("{}", &out);
+                write_if_needed(&out)?;
+            } else if args.json {
+                let result = JsonResult {
+                    modulus: n.to_string(),
+                    factor_1: p.to_string(),
+                    factor_2: q.to_string(),
+                    iterations: iterations.to_string(),
+                    time_ms: duration.as_millis(),
+                };
+                let out = serde_json::to_string_pretty(&result)?;
+                println!("{}", &out);
+                write_if_needed(&out)?;
+            } else if args.time_only {
+                let out = duration.as_millis().to_string();
+                println!("{}", &out);
+                write_if_needed(&out)?;
+            } else if args.quiet {
+                let out = format!("{} {}", p, q);
+                println!("{}", &out);
+                write_if_needed(&out)?;
+            } else {
+                let out = format!(
+                    "\n✅ Factors of {}:\n\np = {}\nq = {}\n⏱️  Execution time: {:?}",
+                    n, p, q, duration
+                );
+                println!("{}", &out);
+                write_if_needed(&out)?;
+            }
+        }
+        Err(failure) => print_failure(
+            &n,
+            &failure,
+            method,
+            &iterations,
+            duration,
+            args,
+            write_if_needed,
+        )?,
     }
 
     Ok(())
@@ -223,6 +785,26 @@ fn main() -> Result<()> {
 
     let prec = args.prec.unwrap_or(30);
 
+    if (args.max_iter.is_some() || args.max_gap.is_some())
+        && args.split_method() != split::Method::Fermat
+    {
+        return Err(anyhow!(
+            "--max-iter and --max-gap only apply to the plain Fermat search (--method fermat)"
+        ));
+    }
+
+    if (args.checkpoint.is_some() || args.resume.is_some()) && !args.split_method().is_fermat() {
+        return Err(anyhow!(
+            "Checkpoints are only supported for the Fermat search (optionally with --ratio)"
+        ));
+    }
+
+    if let Some(t) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(t)
+            .build_global()?;
+    }
+
     if args.stdin || args.input.is_some() {
         let inputs: Vec<String> = if args.stdin {
             io::stdin()
@@ -240,12 +822,6 @@ fn main() -> Result<()> {
                 .collect()
         };
 
-        if let Some(t) = args.threads {
-            rayon::ThreadPoolBuilder::new()
-                .num_threads(t)
-                .build_global()?;
-        }
-
         let pb = if !args.is_quiet() {
             let pb = ProgressBar::new(inputs.len() as u64);
             pb.set_style(
@@ -267,7 +843,7 @@ fn main() -> Result<()> {
                 }
             };
             let iter = Integer::from(1);
-            let _ = factor_and_print(n, iter, prec, &args, &write_if_needed);
+            let _ = factor_and_print(n, iter.clone(), &iter, prec, &args, &write_if_needed);
             if let Some(ref pb) = pb {
                 pb.inc(1);
             }
@@ -276,6 +852,40 @@ fn main() -> Result<()> {
         if let Some(ref pb) = pb {
             pb.finish_with_message("Done");
         }
+    } else if let Some(ref path) = args.resume {
+        let checkpoint = Checkpoint::load(Path::new(path))
+            .map_err(|e| anyhow!("Failed to read checkpoint {}: {e}", path))?;
+        let method = checkpoint_method(args.split_method());
+        if checkpoint.method != method {
+            return Err(anyhow!(
+                "Checkpoint {} was written by {}, not {}; pass the same --method and --ratio",
+                path,
+                checkpoint.method,
+                method
+            ));
+        }
+        if !checkpoint.is_consistent(&fermat_multiplier(args.split_method())) {
+            return Err(anyhow!(
+                "Checkpoint {} is corrupted: a = {} is not the value of iteration {}",
+                path,
+                checkpoint.a,
+                checkpoint.iteration
+            ));
+        }
+        if !args.is_quiet() {
+            println!(
+                "🔁 Resuming {} from iteration {} (saved at Unix time {})",
+                checkpoint.n, checkpoint.iteration, checkpoint.timestamp
+            );
+        }
+        factor_and_print(
+            checkpoint.n,
+            checkpoint.iteration,
+            &checkpoint.first,
+            prec,
+            &args,
+            &write_if_needed,
+        )?;
     } else {
         if args.modulus.is_some() || !atty::is(atty::Stream::Stdin) {
             // Single run: either from flag or piped input
@@ -294,7 +904,7 @@ fn main() -> Result<()> {
                 Integer::from(1)
             };
 
-            factor_and_print(n, iter, prec, &args, &write_if_needed)?;
+            factor_and_print(n, iter.clone(), &iter, prec, &args, &write_if_needed)?;
         } else if args.is_quiet() {
             return Err(anyhow!(
                 "Modulus must be provided in quiet/json/csv/time-only mode (prompts are disabled)"
@@ -322,7 +932,7 @@ fn main() -> Result<()> {
                     Integer::from(1)
                 };
 
-                factor_and_print(n, iter.clone(), prec, &args, &write_if_needed)?;
+                factor_and_print(n, iter.clone(), &iter, prec, &args, &write_if_needed)?;
             }
         }
     }
@@ -0,0 +1,129 @@
+use crate::legendre::{QR_11, QR_63, QR_64, QR_65, QR_ODD_MODULUS};
+use crate::squfof::isqrt;
+use crate::wheel::{Wheel, WHEEL_PRIMES};
+use malachite::Integer;
+
+/// Number of `a` values scanned with the plain parity loop before the residue [`Wheel`]
+/// is built.
+///
+/// Building the wheel takes about as long as scanning this many values plainly, and most
+/// small cofactors are done well before that.
+pub const PLAIN_SPAN: u64 = 1 << 16;
+
+/// Bound on `a` in [`scan`], keeping `a² − n` below `2^126`.
+///
+/// Every odd composite and every multiple of 4 below `2^64` has its Fermat solution at
+/// `a ≤ n/4 + 1 < 2^62`, so nothing is lost by stopping here.
+const A_LIMIT: u64 = 1 << 63;
+
+/// Steps between calls to the `batch` callback of [`scan`].
+const BATCH_STEPS: u64 = 1024;
+
+/// `2^64 mod (63 · 65 · 11)`, for reducing the high word of a `u128`.
+const TWO_64_MOD: u64 = ((1u128 << 64) % QR_ODD_MODULUS as u128) as u64;
+
+/// Returns `√x` if `x` is a perfect square.
+///
+/// The packed tables modulo 64, 63, 65 and 11 reject almost every non-square before the
+/// root is taken. Valid for `x < 2^126`.
+pub fn sqrt_exact_u128(x: u128) -> Option<u64> {
+    if !QR_64.contains(x as u64) {
+        return None;
+    }
+    let high = ((x >> 64) as u64) % QR_ODD_MODULUS;
+    let low = (x as u64) % QR_ODD_MODULUS;
+    let r = (high * TWO_64_MOD + low) % QR_ODD_MODULUS;
+    if !(QR_63.contains(r) && QR_65.contains(r) && QR_11.contains(r)) {
+        return None;
+    }
+    let root = isqrt(x);
+    (root * root == x).then_some(root as u64)
+}
+
+/// The Fermat loop on machine words: scans the `a` values in `[a0, a0 + len)` and returns
+/// the first hit as `(offset, a, x)` with `a² − n = x²`.
+///
+/// Without a prebuilt wheel, the first [`PLAIN_SPAN`] values are scanned with no setup at
+/// all, two at a time for odd `n` since only one parity of `a` makes `a² − n` a square
+/// modulo 4. Longer searches then build the residue [`Wheel`] and continue through it.
+/// With a prebuilt wheel, the whole range goes through the wheel.
+/// `batch` is called with the current offset, `a` and `a² − n` every few thousand values;
+/// returning `false` stops the search.
+///
+/// # Arguments
+/// * `n` - The number to factor, below `2^64`.
+/// * `wheel` - A prebuilt wheel for `n`, or `None` to start plain and build one only if needed.
+/// * `a0` - The first `a` to test, at least `⌈√n⌉`.
+/// * `len` - The number of `a` values to scan.
+/// * `batch` - Progress and cancellation callback.
+///
+/// # Returns
+/// `Some((offset, a, x))` with `a = a0 + offset` for the first hit, otherwise `None`.
+pub fn scan<F>(
+    n: u64,
+    wheel: Option<&Wheel>,
+    a0: u64,
+    len: u64,
+    mut batch: F,
+) -> Option<(u64, u64, u64)>
+where
+    F: FnMut(u64, u64, u128) -> bool,
+{
+    let square = |a: u64| (a as u128) * (a as u128) - n as u128;
+    let end = a0.saturating_add(len).min(A_LIMIT);
+    let plain_end = match wheel {
+        Some(_) => a0,
+        None => a0.saturating_add(PLAIN_SPAN).min(end),
+    };
+
+    // For odd n, a² − n ≡ a² − 1 (mod 4) must be 0 or 1, so a is odd exactly when n ≡ 1 (mod 4).
+    let (mut a, stride) = match n % 4 {
+        1 if a0.is_multiple_of(2) => (a0 + 1, 2),
+        3 if !a0.is_multiple_of(2) => (a0 + 1, 2),
+        1 | 3 => (a0, 2),
+        _ => (a0, 1),
+    };
+    let mut x2 = square(a);
+    let mut steps = 0u64;
+    while a < plain_end {
+        if steps.is_multiple_of(BATCH_STEPS) && !batch(a - a0, a, x2) {
+            return None;
+        }
+        if let Some(x) = sqrt_exact_u128(x2) {
+            return Some((a - a0, a, x));
+        }
+        // (a + s)² − n = a² − n + s·(2a + s)
+        x2 += stride as u128 * (2 * a as u128 + stride as u128);
+        a += stride;
+        steps += 1;
+    }
+    if plain_end >= end {
+        return None;
+    }
+
+    let built;
+    let wheel = match wheel {
+        Some(wheel) => wheel,
+        None => {
+            built = Wheel::new(&Integer::from(n), WHEEL_PRIMES);
+            &built
+        }
+    };
+    let (mut index, skip) = wheel.start(&Integer::from(plain_end));
+    let mut a = plain_end + skip;
+    let mut x2 = square(a);
+    while a < end {
+        if steps.is_multiple_of(BATCH_STEPS) && !batch(a - a0, a, x2) {
+            return None;
+        }
+        if let Some(x) = sqrt_exact_u128(x2) {
+            return Some((a - a0, a, x));
+        }
+        // (a + d)² − n = a² − n + d·(2a + d)
+        let d = wheel.advance(&mut index);
+        x2 += d as u128 * (2 * a as u128 + d as u128);
+        a += d;
+        steps += 1;
+    }
+    None
+}
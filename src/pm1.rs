@@ -0,0 +1,180 @@
+use crate::cancel::CancelToken;
+use crate::prime::{is_probable_prime, primes_up_to, PrimeRange};
+use malachite::base::num::arithmetic::traits::{Gcd, ModMul, ModPow, ModSub, Parity};
+use malachite::base::num::basic::traits::{One, Two, Zero};
+use malachite::base::num::logic::traits::SignificantBits;
+use malachite::{Integer, Natural};
+
+/// Default stage 1 bound.
+pub const DEFAULT_B1: u64 = 100_000;
+
+/// Default stage 2 bound.
+pub const DEFAULT_B2: u64 = 5_000_000;
+
+/// Giant-step width of stage 2. A primorial keeps the baby-step table small.
+pub const STAGE2_D: u64 = 2310;
+
+/// Stage 1 base. `2` is avoided since it has tiny order modulo Mersenne and Fermat factors.
+pub const BASE: u64 = 3;
+
+/// Bit size at which the accumulated stage 1 exponent is applied to the base.
+pub(crate) const EXPONENT_BITS: u64 = 4096;
+
+/// Number of stage 2 primes multiplied together between GCDs.
+const STAGE2_BATCH: u64 = 1024;
+
+/// Largest power of `p` that does not exceed `bound`.
+pub(crate) fn max_power(p: u64, bound: u64) -> u64 {
+    let mut pk = p;
+    while pk <= bound / p {
+        pk *= p;
+    }
+    pk
+}
+
+/// Attempts to factor `n` using Pollard's p−1 method.
+///
+/// Stage 1 raises [`BASE`] to every prime power `≤ b1`, batching the exponents into one
+/// modular exponentiation. If a batch makes the GCD collapse to `n`, the batch is
+/// replayed one prime at a time. Stage 2 looks for one extra prime `q` in `(b1, b2]`
+/// with a baby-step/giant-step walk: writing `q = k·D − j`, it multiplies together
+/// `a^(kD) − a^j` for a table of baby steps `a^j` and giant steps `a^(kD)`. The primes
+/// dividing `D` have no baby step and contribute `a^q − 1` directly. Stage 2 primes come
+/// from a segmented sieve, so memory does not grow with `b2`.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `b1` - Stage 1 bound.
+/// * `b2` - Stage 2 bound. Stage 2 is skipped if `b2 ≤ b1`.
+/// * `iteration` - A mutable counter tracking the number of primes processed.
+/// * `cancel` - Checked between exponent batches and stage 2 GCDs; stops the search once cancelled.
+///
+/// # Returns
+/// `Some((p, q))` with `p ≤ q` if a non-trivial factor pair is found, otherwise `None`.
+pub fn pollard_pm1(
+    n: &Integer,
+    b1: u64,
+    b2: u64,
+    iteration: &mut Integer,
+    cancel: &CancelToken,
+) -> Option<(Integer, Integer)> {
+    if *n < 4 || is_probable_prime(n) {
+        return None;
+    }
+    let n = n.unsigned_abs_ref();
+    if n.even() {
+        return Some((Integer::TWO, Integer::from(n >> 1)));
+    }
+
+    let primes = primes_up_to(b1);
+
+    // Stage 1
+    let mut a = Natural::from(BASE);
+    let mut start = 0;
+    while start < primes.len() {
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let mut end = start;
+        let mut exponent = Natural::ONE;
+        while end < primes.len() && exponent.significant_bits() < EXPONENT_BITS {
+            exponent *= Natural::from(max_power(primes[end], b1));
+            end += 1;
+        }
+
+        let next = (&a).mod_pow(&exponent, n);
+        *iteration += Integer::from(end - start);
+        let g = split(&next, n);
+        if g == *n {
+            // Too many primes at once: replay the batch prime by prime.
+            for &p in &primes[start..end] {
+                a = a.mod_pow(Natural::from(max_power(p, b1)), n);
+                let g = split(&a, n);
+                if g == *n {
+                    return None;
+                }
+                if g != Natural::ONE {
+                    return Some(pair(g, n));
+                }
+            }
+            return None;
+        }
+        if g != Natural::ONE {
+            return Some(pair(g, n));
+        }
+        a = next;
+        start = end;
+    }
+
+    // Stage 2
+    if b2 <= b1 {
+        return None;
+    }
+    let baby_steps = baby_steps(&a, n);
+    let giant = (&a).mod_pow(Natural::from(STAGE2_D), n);
+    let mut k = b1 / STAGE2_D + 1;
+    let mut giant_k = (&giant).mod_pow(Natural::from(k), n);
+    let mut acc = Natural::ONE;
+    let mut batched = 0;
+
+    for q in PrimeRange::new(b1, b2) {
+        if STAGE2_D.is_multiple_of(q) {
+            let term = (&a).mod_pow(Natural::from(q), n);
+            acc = acc.mod_mul(term.mod_sub(Natural::ONE, n), n);
+        } else {
+            while q > k * STAGE2_D {
+                k += 1;
+                giant_k = giant_k.mod_mul(&giant, n);
+            }
+            let j = (k * STAGE2_D - q) as usize;
+            acc = acc.mod_mul((&giant_k).mod_sub(&baby_steps[j], n), n);
+        }
+        *iteration += Integer::ONE;
+
+        batched += 1;
+        if batched == STAGE2_BATCH {
+            batched = 0;
+            let g = (&acc).gcd(n);
+            if g != Natural::ONE {
+                return (g != *n).then(|| pair(g, n));
+            }
+            if cancel.is_cancelled() {
+                return None;
+            }
+        }
+    }
+
+    let g = acc.gcd(n);
+    (g != Natural::ONE && g != *n).then(|| pair(g, n))
+}
+
+/// Baby-step table `a^j mod n` for every `0 < j < D` coprime to `D`; other slots hold zero.
+fn baby_steps(a: &Natural, n: &Natural) -> Vec<Natural> {
+    let mut table = vec![Natural::ZERO; STAGE2_D as usize];
+    let mut power = Natural::ONE;
+    for j in 1..STAGE2_D {
+        power = power.mod_mul(a, n);
+        if j.gcd(STAGE2_D) == 1 {
+            table[j as usize] = power.clone();
+        }
+    }
+    table
+}
+
+/// `gcd(a − 1, n)`.
+fn split(a: &Natural, n: &Natural) -> Natural {
+    if *a == Natural::ZERO {
+        return n.clone();
+    }
+    (a - Natural::ONE).gcd(n)
+}
+
+/// Orders a factor `g` of `n` and its cofactor.
+pub(crate) fn pair(g: Natural, n: &Natural) -> (Integer, Integer) {
+    let cofactor = n / &g;
+    if g <= cofactor {
+        (Integer::from(g), Integer::from(cofactor))
+    } else {
+        (Integer::from(cofactor), Integer::from(g))
+    }
+}
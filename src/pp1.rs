@@ -0,0 +1,120 @@
+use crate::cancel::CancelToken;
+use crate::pm1::{max_power, pair, EXPONENT_BITS};
+use crate::prime::{is_probable_prime, primes_up_to};
+use malachite::base::num::arithmetic::traits::{Gcd, ModMul, ModSquare, ModSub, Parity};
+use malachite::base::num::basic::traits::{One, Two, Zero};
+use malachite::base::num::logic::traits::{BitAccess, SignificantBits};
+use malachite::{Integer, Natural};
+
+/// Default stage 1 bound.
+pub const DEFAULT_B1: u64 = 100_000;
+
+/// Starting values `A` tried in turn. Their discriminants `A² − 4` (5, 3, 21, 2, 77)
+/// fall in distinct square classes, so each seed has an independent chance that
+/// `A² − 4` is a non-residue modulo the hidden factor.
+pub const SEEDS: &[u64] = &[3, 4, 5, 6, 9];
+
+/// Computes the Lucas sequence value `V_m(A) mod n` with `V_0 = 2`, `V_1 = A`
+/// and `V_{k+1} = A·V_k − V_{k−1}`.
+///
+/// Uses the Montgomery ladder on `(V_k, V_{k+1})`:
+/// `V_{2k} = V_k² − 2` and `V_{2k+1} = V_k·V_{k+1} − A`.
+pub fn lucas_v(a: &Natural, m: &Natural, n: &Natural) -> Natural {
+    if *m == Natural::ZERO {
+        return Natural::TWO % n;
+    }
+    let two = Natural::TWO % n;
+    let mut x = a.clone();
+    let mut y = a.mod_square(n).mod_sub(&two, n);
+    for bit in (0..m.significant_bits() - 1).rev() {
+        let xy = (&x).mod_mul(&y, n).mod_sub(a, n);
+        if m.get_bit(bit) {
+            x = xy;
+            y = y.mod_square(n).mod_sub(&two, n);
+        } else {
+            y = xy;
+            x = x.mod_square(n).mod_sub(&two, n);
+        }
+    }
+    x
+}
+
+/// Attempts to factor `n` using Williams' p+1 method.
+///
+/// For each starting value in [`SEEDS`], replaces `A` by `V_m(A)` for every prime
+/// power `m ≤ b1` (using `V_{mk}(A) = V_m(V_k(A))`) and checks `gcd(A − 2, n)`.
+/// Exponents are batched like in [`pollard_pm1`](crate::pm1::pollard_pm1); a batch
+/// that collapses the GCD to `n` is replayed prime by prime before moving on.
+///
+/// A prime factor `p` is found when `p + 1` is `b1`-smooth and `A² − 4` is a
+/// non-residue modulo `p` (when it is a residue, the method finds smooth `p − 1` instead).
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `b1` - Stage 1 bound.
+/// * `iteration` - A mutable counter tracking the number of primes processed.
+/// * `cancel` - Checked between exponent batches; stops the search once cancelled.
+///
+/// # Returns
+/// `Some((p, q))` with `p ≤ q` if a non-trivial factor pair is found, otherwise `None`.
+pub fn williams_pp1(
+    n: &Integer,
+    b1: u64,
+    iteration: &mut Integer,
+    cancel: &CancelToken,
+) -> Option<(Integer, Integer)> {
+    if *n < 4 || is_probable_prime(n) {
+        return None;
+    }
+    let n = n.unsigned_abs_ref();
+    if n.even() {
+        return Some((Integer::TWO, Integer::from(n >> 1)));
+    }
+
+    let primes = primes_up_to(b1);
+
+    'seeds: for &seed in SEEDS {
+        let mut a = Natural::from(seed) % n;
+        let mut start = 0;
+        while start < primes.len() {
+            if cancel.is_cancelled() {
+                return None;
+            }
+            let mut end = start;
+            let mut exponent = Natural::ONE;
+            while end < primes.len() && exponent.significant_bits() < EXPONENT_BITS {
+                exponent *= Natural::from(max_power(primes[end], b1));
+                end += 1;
+            }
+
+            let next = lucas_v(&a, &exponent, n);
+            *iteration += Integer::from(end - start);
+            let g = split(&next, n);
+            if g == *n {
+                for &p in &primes[start..end] {
+                    a = lucas_v(&a, &Natural::from(max_power(p, b1)), n);
+                    let g = split(&a, n);
+                    if g == *n {
+                        continue 'seeds;
+                    }
+                    if g != Natural::ONE {
+                        return Some(pair(g, n));
+                    }
+                }
+                continue 'seeds;
+            }
+            if g != Natural::ONE {
+                return Some(pair(g, n));
+            }
+            a = next;
+            start = end;
+        }
+    }
+
+    None
+}
+
+/// `gcd(V − 2, n)`.
+fn split(v: &Natural, n: &Natural) -> Natural {
+    v.mod_sub(Natural::TWO % n, n).gcd(n)
+}
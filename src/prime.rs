@@ -0,0 +1,227 @@
+use crate::sqrt::sqrt_exact;
+use crate::squfof::isqrt;
+use malachite::base::num::arithmetic::traits::{
+    JacobiSymbol, ModAdd, ModMul, ModPow, ModSquare, ModSub, Parity, UnsignedAbs,
+};
+use malachite::base::num::basic::traits::{One, Two, Zero};
+use malachite::base::num::logic::traits::{BitAccess, SignificantBits};
+use malachite::{Integer, Natural};
+
+/// Small primes used for trial division before the probabilistic tests.
+pub const SMALL_PRIMES: &[u64] = &[
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// Fixed Miller–Rabin bases. Together they are deterministic for all `n < 3.3 · 10²⁴`.
+pub const MILLER_RABIN_BASES: &[u64] = &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/// Strong probable-prime test for `n` to the given `base`.
+///
+/// `n` must be odd and greater than `base`.
+pub fn miller_rabin(n: &Natural, base: u64) -> bool {
+    let n_minus_1 = n - Natural::ONE;
+    let s = n_minus_1.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_1 >> s;
+
+    let mut x = Natural::from(base).mod_pow(&d, n);
+    if x == Natural::ONE || x == n_minus_1 {
+        return true;
+    }
+    for _ in 1..s {
+        x = x.mod_square(n);
+        if x == n_minus_1 {
+            return true;
+        }
+        if x == Natural::ONE {
+            return false;
+        }
+    }
+    false
+}
+
+/// Strong Lucas probable-prime test with Selfridge's parameters (method A).
+///
+/// `n` must be odd, greater than 2 and not a perfect square.
+pub fn strong_lucas(n: &Natural) -> bool {
+    // Find the first D in 5, -7, 9, -11, ... with (D/n) = -1.
+    let n_int = Integer::from(n.clone());
+    let mut d = 5i64;
+    loop {
+        let j = Integer::from(d).jacobi_symbol(&n_int);
+        if j == -1 {
+            break;
+        }
+        if j == 0 && n_int != d.unsigned_abs() {
+            return false;
+        }
+        d = if d > 0 { -(d + 2) } else { -d + 2 };
+    }
+
+    let to_residue = |v: i64| -> Natural {
+        let r = Integer::from(v) % &n_int;
+        if r < 0 {
+            (r + &n_int).unsigned_abs()
+        } else {
+            r.unsigned_abs()
+        }
+    };
+    let d_mod = to_residue(d);
+    let q_mod = to_residue((1 - d) / 4);
+    let half = |x: Natural| -> Natural {
+        if x.odd() {
+            (x + n) >> 1
+        } else {
+            x >> 1
+        }
+    };
+
+    // n + 1 = k · 2^s with k odd
+    let n_plus_1 = n + Natural::ONE;
+    let s = n_plus_1.trailing_zeros().unwrap_or(0);
+    let k = &n_plus_1 >> s;
+
+    // P = 1: U_1 = 1, V_1 = P, Q^1 = Q
+    let mut u = Natural::ONE;
+    let mut v = Natural::ONE;
+    let mut qk = q_mod.clone();
+    for bit in (0..k.significant_bits() - 1).rev() {
+        u = u.mod_mul(&v, n);
+        v = v.mod_square(n).mod_sub((&qk << 1u32) % n, n);
+        qk = qk.mod_square(n);
+        if k.get_bit(bit) {
+            let u_next = half((&u).mod_add(&v, n));
+            let v_next = half((&d_mod).mod_mul(&u, n).mod_add(&v, n));
+            u = u_next;
+            v = v_next;
+            qk = qk.mod_mul(&q_mod, n);
+        }
+    }
+
+    if u == Natural::ZERO || v == Natural::ZERO {
+        return true;
+    }
+    for _ in 1..s {
+        v = v.mod_square(n).mod_sub((&qk << 1u32) % n, n);
+        if v == Natural::ZERO {
+            return true;
+        }
+        qk = qk.mod_square(n);
+    }
+    false
+}
+
+/// Baillie–PSW probable-prime test.
+///
+/// Runs trial division by [`SMALL_PRIMES`], Miller–Rabin with the fixed
+/// [`MILLER_RABIN_BASES`] and a strong Lucas test. No composite is known to pass.
+pub fn is_probable_prime(n: &Integer) -> bool {
+    if *n < Integer::TWO {
+        return false;
+    }
+    let n = n.unsigned_abs_ref();
+
+    for &p in SMALL_PRIMES {
+        let p = Natural::from(p);
+        if *n == p {
+            return true;
+        }
+        if (n % &p) == Natural::ZERO {
+            return false;
+        }
+    }
+
+    for &base in MILLER_RABIN_BASES {
+        if !miller_rabin(n, base) {
+            return false;
+        }
+    }
+
+    if sqrt_exact(&Integer::from(n.clone())).0 {
+        return false;
+    }
+    strong_lucas(n)
+}
+
+/// Length of the window sieved at a time by [`PrimeRange`].
+pub const SEGMENT: u64 = 1 << 18;
+
+/// Returns all primes `≤ limit` using the sieve of Eratosthenes.
+pub fn primes_up_to(limit: u64) -> Vec<u64> {
+    if limit < 2 {
+        return Vec::new();
+    }
+    let limit = limit as usize;
+    let mut composite = vec![false; limit + 1];
+    let mut primes = Vec::new();
+    for i in 2..=limit {
+        if composite[i] {
+            continue;
+        }
+        primes.push(i as u64);
+        let mut j = i * i;
+        while j <= limit {
+            composite[j] = true;
+            j += i;
+        }
+    }
+    primes
+}
+
+/// The primes in `(lo, hi]`, in increasing order, from a segmented sieve.
+///
+/// Only the primes up to `√hi` and one window of [`SEGMENT`] numbers are held at a time,
+/// so a range reaching `10^11` or more costs a few megabytes instead of `hi` bytes.
+pub struct PrimeRange {
+    base: Vec<u64>,
+    /// Start of the next window, or `None` once `hi` has been sieved.
+    low: Option<u64>,
+    hi: u64,
+    window: Vec<u64>,
+    next: usize,
+}
+
+impl PrimeRange {
+    /// Starts the primes in `(lo, hi]`.
+    pub fn new(lo: u64, hi: u64) -> Self {
+        PrimeRange {
+            base: primes_up_to(isqrt(hi as u128) as u64),
+            low: (lo < hi).then(|| lo + 1),
+            hi,
+            window: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Sieves the window `[low, low + SEGMENT)` clipped to `hi`, and moves `low` past it.
+    fn sieve(&mut self, low: u64) {
+        let high = low.saturating_add(SEGMENT - 1).min(self.hi);
+        let mut composite = vec![false; (high - low + 1) as usize];
+        for &p in &self.base {
+            if p * p > high {
+                break;
+            }
+            let mut m = (p * p).max(low.div_ceil(p) * p);
+            while m <= high {
+                composite[(m - low) as usize] = true;
+                m += p;
+            }
+        }
+        self.window = (low.max(2)..=high)
+            .filter(|&m| !composite[(m - low) as usize])
+            .collect();
+        self.next = 0;
+        self.low = (high < self.hi).then(|| high + 1);
+    }
+}
+
+impl Iterator for PrimeRange {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.next == self.window.len() {
+            self.sieve(self.low?);
+        }
+        self.next += 1;
+        Some(self.window[self.next - 1])
+    }
+}
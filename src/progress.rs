@@ -0,0 +1,72 @@
+use crate::factor::verbose;
+use malachite::Integer;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// A snapshot of a running Fermat search.
+#[derive(Clone, Copy, Debug)]
+pub struct Progress<'a> {
+    /// The iteration about to be tested, counted as in
+    /// [`difference_of_squares`](crate::factor::difference_of_squares).
+    pub iteration: &'a Integer,
+    /// The current `a`.
+    pub a: &'a Integer,
+    /// The candidate factor `a − ⌊√(a² − n)⌋`.
+    pub p: &'a Integer,
+    /// The candidate factor `a + ⌊√(a² − n)⌋`.
+    pub q: &'a Integer,
+    /// Time since the search started.
+    pub elapsed: Duration,
+}
+
+/// Receives progress from the Fermat searches instead of them printing it.
+///
+/// [`Terminal`] reproduces the CLI output and [`Silent`] drops everything. Searches call
+/// [`update`](ProgressSink::update) every million iterations or so, and
+/// [`found`](ProgressSink::found) once with the factors when they succeed.
+pub trait ProgressSink {
+    /// Called periodically while the search runs.
+    fn update(&self, progress: &Progress);
+
+    /// Called once when the search finds a factor pair, with `p` and `q` the factors.
+    fn found(&self, _progress: &Progress) {}
+}
+
+/// Discards all progress.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Silent;
+
+impl ProgressSink for Silent {
+    fn update(&self, _progress: &Progress) {}
+}
+
+/// Prints progress to stdout in scientific notation with `prec` digits, overwriting one
+/// line while the search runs.
+#[derive(Clone, Copy, Debug)]
+pub struct Terminal {
+    pub prec: u64,
+}
+
+impl ProgressSink for Terminal {
+    fn update(&self, progress: &Progress) {
+        verbose(progress.iteration, progress.p, progress.q, self.prec);
+        print!("\r");
+        io::stdout().flush().unwrap();
+    }
+
+    fn found(&self, progress: &Progress) {
+        println!();
+        verbose(progress.iteration, progress.p, progress.q, self.prec);
+        println!();
+    }
+}
+
+/// The sink behind the `prec` and `quiet` arguments of the simpler entry points:
+/// [`Terminal`] output, or [`Silent`] when `quiet`.
+pub fn terminal(prec: u64, quiet: bool) -> Box<dyn ProgressSink> {
+    if quiet {
+        Box::new(Silent)
+    } else {
+        Box::new(Terminal { prec })
+    }
+}
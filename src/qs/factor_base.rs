@@ -0,0 +1,179 @@
+use crate::prime::primes_up_to;
+use malachite::Natural;
+
+/// Odd squarefree multipliers considered by the Knuth–Schroeppel selection.
+pub const MULTIPLIERS: &[u64] = &[
+    1, 3, 5, 7, 11, 13, 15, 17, 19, 21, 23, 29, 31, 33, 35, 37, 39, 41, 43, 47, 51, 53, 55, 57, 59,
+    61, 65, 67, 69, 71, 73,
+];
+
+/// `b^e mod m` on machine words.
+pub fn mod_pow_u64(b: u64, mut e: u64, m: u64) -> u64 {
+    let m128 = m as u128;
+    let mut base = b as u128 % m128;
+    let mut result = 1u128 % m128;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = result * base % m128;
+        }
+        base = base * base % m128;
+        e >>= 1;
+    }
+    result as u64
+}
+
+/// Inverse of `a` modulo `m`, if it exists.
+pub fn mod_inverse_u64(a: u64, m: u64) -> Option<u64> {
+    let (mut old_r, mut r) = (a as i128 % m as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    (old_r == 1).then(|| old_s.rem_euclid(m as i128) as u64)
+}
+
+/// Square root of `n` modulo an odd prime `p` by Tonelli–Shanks, if `n` is a residue.
+pub fn sqrt_mod(n: u64, p: u64) -> Option<u64> {
+    let n = n % p;
+    if n == 0 {
+        return Some(0);
+    }
+    if mod_pow_u64(n, (p - 1) / 2, p) != 1 {
+        return None;
+    }
+    if p % 4 == 3 {
+        return Some(mod_pow_u64(n, (p + 1) / 4, p));
+    }
+
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let mut z = 2;
+    while mod_pow_u64(z, (p - 1) / 2, p) != p - 1 {
+        z += 1;
+    }
+
+    let mul = |a: u64, b: u64| ((a as u128 * b as u128) % p as u128) as u64;
+    let mut m = s;
+    let mut c = mod_pow_u64(z, q, p);
+    let mut t = mod_pow_u64(n, q, p);
+    let mut r = mod_pow_u64(n, q.div_ceil(2), p);
+    while t != 1 {
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != 1 {
+            t2 = mul(t2, t2);
+            i += 1;
+        }
+        let b = mod_pow_u64(c, 1 << (m - i - 1), p);
+        m = i;
+        c = mul(b, b);
+        t = mul(t, c);
+        r = mul(r, b);
+    }
+    Some(r)
+}
+
+/// `n mod p` for a little-endian limb slice.
+pub fn rem_limbs(limbs: &[u64], p: u64) -> u64 {
+    let mut r = 0u128;
+    for &limb in limbs.iter().rev() {
+        r = ((r << 64) | limb as u128) % p as u128;
+    }
+    r as u64
+}
+
+/// `n mod p` for a `Natural`.
+pub fn rem_natural(n: &Natural, p: u64) -> u64 {
+    rem_limbs(&n.to_limbs_asc(), p)
+}
+
+/// Picks the multiplier `k` that maximizes the Knuth–Schroeppel function, i.e. the
+/// expected contribution of small primes to the smoothness of values modulo `kN`.
+pub fn select_multiplier(n: &Natural) -> u64 {
+    let primes = primes_up_to(1000);
+    let n_mod_8 = rem_natural(n, 8);
+    let residues: Vec<u64> = primes.iter().map(|&p| rem_natural(n, p)).collect();
+
+    let mut best = (f64::MIN, 1);
+    for &k in MULTIPLIERS {
+        let ln2 = std::f64::consts::LN_2;
+        let mut score = -0.5 * (k as f64).ln();
+        score += match (k * n_mod_8) % 8 {
+            1 => 2.0 * ln2,
+            5 => ln2,
+            _ => 0.5 * ln2,
+        };
+        for (&p, &r) in primes.iter().zip(&residues).skip(1) {
+            let ln_p = (p as f64).ln();
+            if k % p == 0 {
+                score += ln_p / p as f64;
+            } else if mod_pow_u64(r * (k % p) % p, (p - 1) / 2, p) == 1 {
+                score += 2.0 * ln_p / (p - 1) as f64;
+            }
+        }
+        if score > best.0 {
+            best = (score, k);
+        }
+    }
+    best.1
+}
+
+/// The primes `p` for which `kN` is a quadratic residue, with `√(kN) mod p` and `log₂ p`.
+///
+/// `primes[0]` is always `2`. Primes dividing `kN` are kept with root `0`.
+#[derive(Clone, Debug)]
+pub struct FactorBase {
+    pub multiplier: u64,
+    pub kn: Natural,
+    pub primes: Vec<u64>,
+    pub sqrts: Vec<u64>,
+    pub logs: Vec<u8>,
+}
+
+impl FactorBase {
+    /// Builds a factor base of `size` primes for `n` and multiplier `k`.
+    pub fn new(n: &Natural, multiplier: u64, size: usize) -> Self {
+        let kn = n * Natural::from(multiplier);
+        let mut primes = vec![2];
+        let mut sqrts = vec![rem_natural(&kn, 2)];
+        let mut limit = (size as u64 * 32).max(1000);
+        'grow: loop {
+            let last = *primes.last().unwrap();
+            for p in primes_up_to(limit).into_iter().skip_while(|&p| p <= last) {
+                if let Some(root) = sqrt_mod(rem_natural(&kn, p), p) {
+                    primes.push(p);
+                    sqrts.push(root);
+                    if primes.len() == size {
+                        break 'grow;
+                    }
+                }
+            }
+            limit *= 2;
+        }
+        let logs = primes
+            .iter()
+            .map(|&p| (p as f64).log2().round() as u8)
+            .collect();
+        FactorBase {
+            multiplier,
+            kn,
+            primes,
+            sqrts,
+            logs,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.primes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.primes.is_empty()
+    }
+
+    pub fn largest(&self) -> u64 {
+        *self.primes.last().unwrap()
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+/// Finds subsets of rows whose sum is zero over GF(2).
+///
+/// Each row lists the column indices holding an odd exponent. Rows containing a column
+/// that no other row touches can never be part of a dependency and are dropped first;
+/// the rest goes through dense Gaussian elimination with a history matrix recording
+/// which original rows were combined.
+///
+/// Returns up to `limit` dependencies as lists of row indices.
+pub fn find_dependencies(rows: &[Vec<usize>], limit: usize) -> Vec<Vec<usize>> {
+    // Singleton removal
+    let mut alive = vec![true; rows.len()];
+    loop {
+        let mut weight: HashMap<usize, usize> = HashMap::new();
+        for (row, _) in rows.iter().zip(&alive).filter(|(_, alive)| **alive) {
+            for &c in row {
+                *weight.entry(c).or_default() += 1;
+            }
+        }
+        let mut removed = false;
+        for (r, row) in rows.iter().enumerate() {
+            if alive[r] && row.iter().any(|c| weight[c] == 1) {
+                alive[r] = false;
+                removed = true;
+            }
+        }
+        if !removed {
+            break;
+        }
+    }
+
+    let kept: Vec<usize> = (0..rows.len()).filter(|&r| alive[r]).collect();
+    let mut columns: Vec<usize> = kept.iter().flat_map(|&r| rows[r].iter().copied()).collect();
+    columns.sort_unstable();
+    columns.dedup();
+
+    let n_rows = kept.len();
+    let n_cols = columns.len();
+    let col_words = n_cols.div_ceil(64);
+    let hist_words = n_rows.div_ceil(64);
+    let stride = col_words + hist_words;
+    let mut matrix = vec![0u64; n_rows * stride];
+
+    for (r, &orig) in kept.iter().enumerate() {
+        let row = &mut matrix[r * stride..(r + 1) * stride];
+        for c in &rows[orig] {
+            let c = columns.binary_search(c).unwrap();
+            row[c / 64] ^= 1 << (c % 64);
+        }
+        row[col_words + r / 64] |= 1 << (r % 64);
+    }
+
+    let mut pivoted = vec![false; n_rows];
+    for c in 0..n_cols {
+        let (word, bit) = (c / 64, 1u64 << (c % 64));
+        let Some(p) = (0..n_rows).find(|&r| !pivoted[r] && matrix[r * stride + word] & bit != 0)
+        else {
+            continue;
+        };
+        pivoted[p] = true;
+        let pivot: Vec<u64> = matrix[p * stride + word..(p + 1) * stride].to_vec();
+        for r in 0..n_rows {
+            if pivoted[r] || matrix[r * stride + word] & bit == 0 {
+                continue;
+            }
+            let row = &mut matrix[r * stride + word..(r + 1) * stride];
+            for (dst, src) in row.iter_mut().zip(&pivot) {
+                *dst ^= src;
+            }
+        }
+    }
+
+    (0..n_rows)
+        .filter(|&r| !pivoted[r])
+        .take(limit)
+        .map(|r| {
+            let hist = &matrix[r * stride + col_words..(r + 1) * stride];
+            (0..n_rows)
+                .filter(|&i| hist[i / 64] >> (i % 64) & 1 == 1)
+                .map(|i| kept[i])
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,282 @@
+pub mod factor_base;
+pub mod linalg;
+pub mod poly;
+pub mod sieve;
+
+use crate::cancel::CancelToken;
+use crate::pm1::pair;
+use crate::prime::is_probable_prime;
+use crate::rho::pollard_rho;
+use factor_base::{rem_natural, select_multiplier, FactorBase};
+use linalg::find_dependencies;
+use malachite::base::num::arithmetic::traits::{AbsDiff, CheckedRoot, Gcd, ModPow, Parity};
+use malachite::base::num::basic::traits::One;
+use malachite::base::num::logic::traits::SignificantBits;
+use malachite::{Integer, Natural};
+use poly::{choose_a, Polynomial, Rng};
+use rayon::prelude::*;
+use sieve::{sieve, trial_divide, Relation};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+/// Relations collected beyond the size of the factor base.
+pub const EXTRA_RELATIONS: usize = 64;
+
+/// Bits subtracted from the sieve threshold to make up for the unsieved small primes.
+const THRESHOLD_SLACK: f64 = 16.0;
+
+/// Inputs up to this many bits are handed to [`pollard_rho`] instead.
+const RHO_BITS: u64 = 64;
+
+/// Sieve bytes checked against the threshold at once before looking at single positions.
+const SCAN_CHUNK: usize = 64;
+
+/// `(digits, factor base size, half-width M, large prime multiplier)`.
+const PARAMS: &[(u32, usize, u64, u64)] = &[
+    (20, 100, 16_384, 30),
+    (30, 200, 16_384, 40),
+    (40, 350, 16_384, 50),
+    (50, 1_200, 32_768, 60),
+    (60, 4_000, 32_768, 80),
+    (70, 10_000, 65_536, 100),
+    (80, 18_000, 65_536, 120),
+    (90, 32_000, 98_304, 150),
+    (100, 50_000, 131_072, 150),
+];
+
+/// Sieve parameters for one input size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Params {
+    /// Number of primes in the factor base.
+    pub fb_size: usize,
+    /// Half-width `M` of the sieve interval `[−M, M)`.
+    pub half_width: u64,
+    /// Cofactors up to `large_multiplier · p_max` are kept as partial relations.
+    pub large_multiplier: u64,
+}
+
+impl Params {
+    /// Parameters for an input with the given number of decimal digits, interpolating
+    /// the factor base size between table rows.
+    pub fn for_digits(digits: u32) -> Self {
+        let upper = PARAMS
+            .iter()
+            .position(|&(d, ..)| d >= digits)
+            .unwrap_or(PARAMS.len() - 1);
+        let (d1, fb1, m, large_multiplier) = PARAMS[upper];
+        let fb_size = if upper == 0 || digits >= d1 {
+            fb1
+        } else {
+            let (d0, fb0, ..) = PARAMS[upper - 1];
+            fb0 + (fb1 - fb0) * (digits - d0) as usize / (d1 - d0) as usize
+        };
+        Params {
+            fb_size,
+            half_width: m,
+            large_multiplier,
+        }
+    }
+}
+
+/// Attempts to factor `n` with the self-initializing quadratic sieve (SIQS).
+///
+/// Chooses a Knuth–Schroeppel multiplier `k`, builds a factor base of primes for which
+/// `kN` is a quadratic residue, and sieves families of polynomials `Q(x) = Ax² + 2Bx + C`
+/// sharing one `A`, switching between them in Gray code order. Relations with a single
+/// large prime are paired up when a second one with the same prime shows up. Once there
+/// are more relations than factor base primes, Gaussian elimination over GF(2) yields
+/// congruences `X² ≡ Y² (mod N)` and `gcd(X − Y, N)` splits `n`.
+///
+/// Polynomial families are sieved in parallel on the rayon thread pool. Perfect powers
+/// are split directly, and inputs of at most 64 bits are handed to [`pollard_rho`].
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `iteration` - A mutable counter tracking the number of polynomials sieved.
+/// * `cancel` - Checked before each polynomial; stops the sieve once cancelled.
+///
+/// # Returns
+/// `Some((p, q))` with `p ≤ q` if a non-trivial factor pair is found, otherwise `None`.
+pub fn quadratic_sieve(
+    n: &Integer,
+    iteration: &mut Integer,
+    cancel: &CancelToken,
+) -> Option<(Integer, Integer)> {
+    if *n < 4 || is_probable_prime(n) {
+        return None;
+    }
+    let n_int = n;
+    let n = n.unsigned_abs_ref();
+    if n.even() {
+        return Some(pair(Natural::from(2u32), n));
+    }
+    for k in 2..n.significant_bits() {
+        if let Some(root) = n.checked_root(k) {
+            return Some(pair(root, n));
+        }
+    }
+    if n.significant_bits() <= RHO_BITS {
+        return pollard_rho(n_int, 2, iteration, cancel);
+    }
+
+    let digits = n.to_string().len() as u32;
+    let params = Params::for_digits(digits);
+    let multiplier = select_multiplier(n);
+    let fb = FactorBase::new(n, multiplier, params.fb_size);
+    if let Some(&p) = fb.primes.iter().find(|&&p| rem_natural(n, p) == 0) {
+        return Some(pair(Natural::from(p), n));
+    }
+
+    let m = params.half_width;
+    let pmax = fb.largest();
+    let large_bound = pmax.saturating_mul(params.large_multiplier.min(pmax));
+    let kn_bits = log2(&fb.kn);
+    let m_bits = (m as f64).log2();
+    let target_bits = 0.5 * (kn_bits + 1.0) - m_bits;
+    let threshold = (m_bits + 0.5 * kn_bits - 0.5 - (large_bound as f64).log2() - THRESHOLD_SLACK)
+        .max(0.0) as u8;
+
+    let mut rng = Rng::new(multiplier ^ digits as u64);
+    let mut used: HashSet<Vec<usize>> = HashSet::new();
+    let mut relations: Vec<Relation> = Vec::new();
+    let mut partials: HashMap<u64, Relation> = HashMap::new();
+    let mut needed = fb.len() + 1 + EXTRA_RELATIONS;
+    let batch = rayon::current_num_threads().max(1);
+
+    loop {
+        while relations.len() < needed {
+            let mut families = Vec::with_capacity(batch);
+            let mut attempts = 0;
+            while families.len() < batch {
+                let a = choose_a(&fb, target_bits, &mut rng)?;
+                if used.insert(a.clone()) {
+                    families.push(a);
+                }
+                attempts += 1;
+                if attempts > 1000 * batch {
+                    return None;
+                }
+            }
+
+            let found: Vec<(u64, Vec<Relation>)> = families
+                .into_par_iter()
+                .map(|a| sieve_family(&fb, a, m, threshold, large_bound, n, cancel))
+                .collect();
+
+            for (polys, rels) in found {
+                *iteration += Integer::from(polys);
+                for rel in rels {
+                    if rel.large == 1 {
+                        relations.push(rel);
+                        continue;
+                    }
+                    match partials.entry(rel.large) {
+                        Entry::Occupied(e) => relations.push(e.get().combine(&rel, n)),
+                        Entry::Vacant(e) => {
+                            e.insert(rel);
+                        }
+                    }
+                }
+            }
+            if cancel.is_cancelled() {
+                return None;
+            }
+        }
+
+        let rows: Vec<Vec<usize>> = relations
+            .iter()
+            .map(|rel| {
+                rel.factors
+                    .iter()
+                    .filter(|(_, e)| e % 2 == 1)
+                    .map(|&(i, _)| i)
+                    .collect()
+            })
+            .collect();
+        for dependency in find_dependencies(&rows, 64) {
+            if let Some(g) = square_root(&dependency, &relations, &fb, n) {
+                return Some(pair(g, n));
+            }
+        }
+        needed += EXTRA_RELATIONS;
+    }
+}
+
+/// Sieves every polynomial sharing the given `A`, or as many as run before `cancel`
+/// fires, and returns how many were sieved together with the relations found.
+fn sieve_family(
+    fb: &FactorBase,
+    a_factors: Vec<usize>,
+    m: u64,
+    threshold: u8,
+    large_bound: u64,
+    n: &Natural,
+    cancel: &CancelToken,
+) -> (u64, Vec<Relation>) {
+    let mut poly = Polynomial::new(fb, a_factors, m);
+    let mut buf = vec![0u8; 2 * m as usize];
+    let mut relations = Vec::new();
+    for index in 0..poly.count {
+        if cancel.is_cancelled() {
+            return (index, relations);
+        }
+        if index > 0 {
+            poly.advance(fb, index);
+        }
+        sieve(&poly, fb, &mut buf);
+        for (chunk, values) in buf.chunks(SCAN_CHUNK).enumerate() {
+            if values.iter().fold(0, |a, &b| a.max(b)) < threshold {
+                continue;
+            }
+            for (offset, &v) in values.iter().enumerate() {
+                if v < threshold {
+                    continue;
+                }
+                let i = chunk * SCAN_CHUNK + offset;
+                if let Some(rel) = trial_divide(&poly, fb, n, i as u64, m, large_bound) {
+                    relations.push(rel);
+                }
+            }
+        }
+    }
+    (poly.count, relations)
+}
+
+/// Builds `X = ∏ yᵢ` and `Y = √(∏ Q(xᵢ))` for a dependency and returns `gcd(X − Y, n)`
+/// if it is a proper factor.
+fn square_root(
+    dependency: &[usize],
+    relations: &[Relation],
+    fb: &FactorBase,
+    n: &Natural,
+) -> Option<Natural> {
+    let mut x = Natural::ONE;
+    let mut y = Natural::ONE;
+    let mut exponents: HashMap<usize, u64> = HashMap::new();
+    for &r in dependency {
+        let rel = &relations[r];
+        x = x * &rel.y % n;
+        y = y * &rel.extra % n;
+        for &(i, e) in &rel.factors {
+            *exponents.entry(i).or_default() += e as u64;
+        }
+    }
+    for (i, e) in exponents {
+        // Index 0 is the sign; an even count of −1 contributes nothing.
+        if i == 0 {
+            continue;
+        }
+        let p = Natural::from(fb.primes[i - 1]) % n;
+        y = y * p.mod_pow(Natural::from(e / 2), n) % n;
+    }
+
+    let g = x.abs_diff(y).gcd(n);
+    (g != Natural::ONE && g != *n).then_some(g)
+}
+
+/// Approximate `log₂ n`.
+fn log2(n: &Natural) -> f64 {
+    let bits = n.significant_bits();
+    let shift = bits.saturating_sub(53);
+    let top = u64::try_from(&(n >> shift)).unwrap_or(u64::MAX);
+    (top as f64).log2() + shift as f64
+}
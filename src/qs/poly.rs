@@ -0,0 +1,224 @@
+use super::factor_base::{mod_inverse_u64, rem_natural, FactorBase};
+use malachite::base::num::arithmetic::traits::Square;
+use malachite::Integer;
+
+/// Smallest prime allowed as a factor of `A`. Tiny primes make poor `A` factors
+/// since they would otherwise be sieved very densely.
+const MIN_A_PRIME: u64 = 400;
+
+/// Ideal bit size of each prime factor of `A`.
+const IDEAL_A_PRIME_BITS: f64 = 11.0;
+
+/// Small xorshift generator; polynomial choice only needs to be varied, not secure.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/// Chooses the factor base indices of the primes whose product forms the next `A`,
+/// aiming for `A ≈ target`. Returns `None` if the factor base is too small.
+pub fn choose_a(fb: &FactorBase, target_bits: f64, rng: &mut Rng) -> Option<Vec<usize>> {
+    let lowest = fb
+        .primes
+        .iter()
+        .position(|&p| p >= MIN_A_PRIME.min(fb.largest() / 8).max(5))?;
+    let highest = fb.len() - 1;
+    let max_bits = (fb.largest() as f64).log2() - 1.0;
+
+    let mut s = (target_bits / IDEAL_A_PRIME_BITS).round().max(1.0) as usize;
+    while target_bits / (s as f64) > max_bits {
+        s += 1;
+    }
+    let prime_bits = target_bits / s as f64;
+
+    // Window of primes around the ideal size, widened until it holds enough choices.
+    let ideal = prime_bits.exp2();
+    let center = fb.primes.partition_point(|&p| (p as f64) < ideal);
+    let mut lo = center.saturating_sub(s * 2).max(lowest);
+    let mut hi = (center + s * 2).min(highest);
+    while hi - lo < s * 3 && (lo > lowest || hi < highest) {
+        lo = lo.saturating_sub(1).max(lowest);
+        hi = (hi + 1).min(highest);
+    }
+    if hi <= lo || hi - lo < s {
+        return None;
+    }
+
+    let mut chosen: Vec<usize> = Vec::with_capacity(s);
+    let mut bits = 0.0;
+    while chosen.len() + 1 < s {
+        let idx = lo + rng.below(hi - lo);
+        if fb.sqrts[idx] != 0 && !chosen.contains(&idx) {
+            bits += (fb.primes[idx] as f64).log2();
+            chosen.push(idx);
+        }
+    }
+
+    // The last prime brings the product as close to the target as possible.
+    let last_ideal = (target_bits - bits).exp2();
+    let mut idx = fb
+        .primes
+        .partition_point(|&p| (p as f64) < last_ideal)
+        .clamp(lowest, highest);
+    let mut step = 0usize;
+    loop {
+        let candidates = [idx + step, idx.wrapping_sub(step)];
+        if let Some(&found) = candidates
+            .iter()
+            .find(|&&i| (lowest..=highest).contains(&i) && fb.sqrts[i] != 0 && !chosen.contains(&i))
+        {
+            idx = found;
+            break;
+        }
+        step += 1;
+        if step > fb.len() {
+            return None;
+        }
+    }
+    chosen.push(idx);
+    chosen.sort_unstable();
+    Some(chosen)
+}
+
+/// A self-initializing polynomial family `Q(x) = Ax² + 2Bx + C` with `B² − kN = AC`,
+/// so that `(Ax + B)² ≡ A·Q(x) (mod N)`.
+///
+/// Sieve positions are stored relative to the start of the interval `[−M, M)`.
+#[derive(Clone, Debug)]
+pub struct Polynomial {
+    pub a: Integer,
+    pub b: Integer,
+    pub c: Integer,
+    kn: Integer,
+    /// Factor base indices of the primes dividing `A`.
+    pub a_factors: Vec<usize>,
+    /// `B_l` terms with `B = Σ ±B_l`.
+    b_terms: Vec<Integer>,
+    /// Current sign of each `B_l` (`true` for minus).
+    negated: Vec<bool>,
+    /// `2·B_l·A⁻¹ mod p` for every term and factor base prime.
+    bainv: Vec<Vec<u64>>,
+    /// First sieve position for every factor base prime (`u64::MAX` if not sieved).
+    pub root1: Vec<u64>,
+    /// Second sieve position for every factor base prime.
+    pub root2: Vec<u64>,
+    /// Number of polynomials available for this `A`.
+    pub count: u64,
+}
+
+impl Polynomial {
+    /// Initializes the first polynomial for the given `A` factors and half-width `m`.
+    pub fn new(fb: &FactorBase, a_factors: Vec<usize>, m: u64) -> Self {
+        let kn = Integer::from(fb.kn.clone());
+        let a = a_factors.iter().fold(Integer::from(1), |acc, &i| {
+            acc * Integer::from(fb.primes[i])
+        });
+
+        let mut b_terms = Vec::with_capacity(a_factors.len());
+        for &i in &a_factors {
+            let q = fb.primes[i];
+            let a_over_q = &a / Integer::from(q);
+            let r = rem_natural(a_over_q.unsigned_abs_ref(), q);
+            let inv = mod_inverse_u64(r, q).expect("A factors are distinct primes");
+            let mut gamma = (fb.sqrts[i] as u128 * inv as u128 % q as u128) as u64;
+            if gamma > q / 2 {
+                gamma = q - gamma;
+            }
+            b_terms.push(a_over_q * Integer::from(gamma));
+        }
+        let b: Integer = b_terms.iter().sum();
+        let c = (b.clone().square() - &kn) / &a;
+
+        let n_primes = fb.len();
+        let mut root1 = vec![u64::MAX; n_primes];
+        let mut root2 = vec![u64::MAX; n_primes];
+        let mut bainv = vec![vec![0u64; n_primes]; b_terms.len()];
+        let a_abs = a.unsigned_abs_ref();
+        let b_abs = b.unsigned_abs_ref();
+
+        for j in 1..n_primes {
+            let p = fb.primes[j];
+            let a_mod = rem_natural(a_abs, p);
+            if a_mod == 0 {
+                continue;
+            }
+            let ainv = mod_inverse_u64(a_mod, p).unwrap() as u128;
+            let p128 = p as u128;
+            for (l, term) in b_terms.iter().enumerate() {
+                let t = rem_natural(term.unsigned_abs_ref(), p) as u128;
+                bainv[l][j] = (2 * t * ainv % p128) as u64;
+            }
+
+            let b_mod = rem_natural(b_abs, p) as u128;
+            let t = fb.sqrts[j] as u128;
+            let shift = m as u128 % p128;
+            let r1 = ainv * ((t + p128 - b_mod) % p128) % p128;
+            let r2 = ainv * ((2 * p128 - t - b_mod) % p128) % p128;
+            root1[j] = ((r1 + shift) % p128) as u64;
+            root2[j] = ((r2 + shift) % p128) as u64;
+        }
+
+        let count = 1u64 << (a_factors.len() - 1);
+        Polynomial {
+            a,
+            b,
+            c,
+            kn,
+            a_factors,
+            negated: vec![false; b_terms.len()],
+            b_terms,
+            bainv,
+            root1,
+            root2,
+            count,
+        }
+    }
+
+    /// Switches to polynomial number `index` (`1 ≤ index < count`) in Gray code order,
+    /// flipping the sign of one `B_l` and updating `C` and every sieve root incrementally.
+    pub fn advance(&mut self, fb: &FactorBase, index: u64) {
+        let v = index.trailing_zeros() as usize;
+        let negate = !self.negated[v];
+        self.negated[v] = negate;
+        let twice = Integer::from(2) * &self.b_terms[v];
+        if negate {
+            self.b -= twice;
+        } else {
+            self.b += twice;
+        }
+        self.c = (self.b.clone().square() - &self.kn) / &self.a;
+
+        for j in 1..fb.len() {
+            if self.root1[j] == u64::MAX {
+                continue;
+            }
+            let p = fb.primes[j];
+            let delta = if negate {
+                self.bainv[v][j]
+            } else {
+                p - self.bainv[v][j]
+            };
+            for root in [&mut self.root1[j], &mut self.root2[j]] {
+                *root += delta;
+                if *root >= p {
+                    *root -= p;
+                }
+            }
+        }
+    }
+}
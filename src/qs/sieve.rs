@@ -0,0 +1,143 @@
+use super::factor_base::{rem_limbs, FactorBase};
+use super::poly::Polynomial;
+use malachite::base::num::arithmetic::traits::{Square, UnsignedAbs};
+use malachite::base::num::basic::traits::Zero;
+use malachite::{Integer, Natural};
+
+/// Primes below this bound are not sieved; the threshold allows for their missing logs.
+pub const SMALL_PRIME_CUTOFF: u64 = 30;
+
+/// A relation `y² ≡ ∏ pᵢ^eᵢ · extra² (mod N)`.
+///
+/// Factor index `0` stands for `−1`; index `j + 1` for factor base prime `j`.
+#[derive(Clone, Debug)]
+pub struct Relation {
+    pub y: Natural,
+    pub factors: Vec<(usize, u32)>,
+    /// Large prime left after trial division (`1` for full relations).
+    pub large: u64,
+    /// Square root of the extra square introduced by combining two partial relations.
+    pub extra: Natural,
+}
+
+impl Relation {
+    /// Combines two partial relations sharing the same large prime into a full one.
+    pub fn combine(&self, other: &Relation, n: &Natural) -> Relation {
+        let mut factors = self.factors.clone();
+        for &(idx, e) in &other.factors {
+            match factors.iter_mut().find(|(i, _)| *i == idx) {
+                Some((_, exp)) => *exp += e,
+                None => factors.push((idx, e)),
+            }
+        }
+        Relation {
+            y: (&self.y * &other.y) % n,
+            factors,
+            large: 1,
+            extra: (&self.extra * &other.extra * Natural::from(self.large)) % n,
+        }
+    }
+}
+
+/// Adds `log₂ p` at every sieve position hit by a root of each factor base prime.
+pub fn sieve(poly: &Polynomial, fb: &FactorBase, buf: &mut [u8]) {
+    buf.fill(0);
+    let len = buf.len() as u64;
+    for j in 1..fb.len() {
+        let p = fb.primes[j];
+        if p < SMALL_PRIME_CUTOFF || poly.root1[j] == u64::MAX {
+            continue;
+        }
+        let log = fb.logs[j];
+        let (r1, r2) = (poly.root1[j], poly.root2[j]);
+        let roots: &[u64] = if r1 == r2 { &[r1] } else { &[r1, r2] };
+        for &root in roots {
+            if root < len {
+                for v in buf[root as usize..].iter_mut().step_by(p as usize) {
+                    *v = v.wrapping_add(log);
+                }
+            }
+        }
+    }
+}
+
+/// Divides `limbs` by `p` in place.
+fn div_limbs(limbs: &mut Vec<u64>, p: u64) {
+    let mut r = 0u128;
+    for limb in limbs.iter_mut().rev() {
+        let cur = (r << 64) | *limb as u128;
+        *limb = (cur / p as u128) as u64;
+        r = cur % p as u128;
+    }
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+/// Trial-divides `A·Q(x)` for sieve position `i` over the factor base.
+///
+/// Returns a full relation, a partial relation with one large prime below `large_bound`,
+/// or `None` if the value is not smooth enough.
+pub fn trial_divide(
+    poly: &Polynomial,
+    fb: &FactorBase,
+    n: &Natural,
+    i: u64,
+    m: u64,
+    large_bound: u64,
+) -> Option<Relation> {
+    let x = Integer::from(i) - Integer::from(m);
+    let y = &poly.a * &x + &poly.b;
+    let q = (&poly.a * x.clone().square()) + Integer::from(2) * &poly.b * &x + &poly.c;
+    if q == Integer::ZERO {
+        return None;
+    }
+
+    let mut factors: Vec<(usize, u32)> = Vec::new();
+    if q < 0 {
+        factors.push((0, 1));
+    }
+    let mut value = q.unsigned_abs();
+    let twos = value.trailing_zeros().unwrap_or(0);
+    if twos > 0 {
+        value >>= twos;
+        factors.push((1, twos as u32));
+    }
+    let mut limbs = value.to_limbs_asc();
+
+    for j in 1..fb.len() {
+        let p = fb.primes[j];
+        let divides_a = poly.root1[j] == u64::MAX;
+        if !divides_a {
+            let pos = i % p;
+            if pos != poly.root1[j] && pos != poly.root2[j] {
+                continue;
+            }
+        }
+        let mut e = 0;
+        while !limbs.is_empty() && rem_limbs(&limbs, p) == 0 {
+            div_limbs(&mut limbs, p);
+            e += 1;
+        }
+        if divides_a {
+            // A·Q(x) carries one more power of each prime of A.
+            e += 1;
+        }
+        if e > 0 {
+            factors.push((j + 1, e));
+        }
+    }
+
+    let large = match limbs.as_slice() {
+        [1] => 1,
+        [rest] if *rest < large_bound => *rest,
+        _ => return None,
+    };
+
+    Some(Relation {
+        y: y.unsigned_abs() % n,
+        factors,
+        large,
+        extra: Natural::from(1u32),
+    })
+}
@@ -0,0 +1,225 @@
+use crate::cancel::CancelToken;
+use crate::factor::{bounded_difference_of_squares, FermatOutcome, SearchLimit};
+use crate::hart::CANCEL_ROUNDS;
+use crate::pm1::pair;
+use crate::prime::is_probable_prime;
+use crate::progress::{Progress, ProgressSink};
+use crate::sqrt::{is_probably_square, sqrt_ceil, sqrt_exact};
+use malachite::base::num::arithmetic::traits::{Gcd, Square};
+use malachite::base::num::basic::traits::One;
+use malachite::{Integer, Natural};
+use std::cell::RefCell;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A factor ratio `u/v`, used when `p ≈ (u/v)·q`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ratio {
+    pub u: u64,
+    pub v: u64,
+}
+
+impl Ratio {
+    /// The multiplier `4·u·v` of the number the Fermat search runs on, computed without
+    /// overflow for any `u` and `v`.
+    pub fn multiplier(&self) -> Integer {
+        Integer::from(4u32) * Integer::from(self.u) * Integer::from(self.v)
+    }
+}
+
+impl FromStr for Ratio {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (u, v) = s
+            .split_once('/')
+            .ok_or_else(|| format!("expected a ratio like 3/5, got `{}`", s))?;
+        let parse = |t: &str| match t.trim().parse::<u64>() {
+            Ok(x) if x > 0 => Ok(x),
+            _ => Err(format!("`{}` is not a positive integer", t.trim())),
+        };
+        Ok(Ratio {
+            u: parse(u)?,
+            v: parse(v)?,
+        })
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.u, self.v)
+    }
+}
+
+/// Every ratio `u/v` in lowest terms with `1 ≤ u ≤ v ≤ k`, ordered by denominator.
+///
+/// `u/v` and `v/u` lead to the same multiplier `4uv`, so only one of them is listed.
+pub fn ratios_up_to(k: u64) -> Vec<Ratio> {
+    let mut ratios = Vec::new();
+    for v in 1..=k {
+        for u in 1..=v {
+            if u.gcd(v) == 1 {
+                ratios.push(Ratio { u, v });
+            }
+        }
+    }
+    ratios
+}
+
+/// Returns `gcd(d, n)` if it is a proper factor of `n`.
+fn proper_gcd(d: &Integer, n: &Natural) -> Option<Natural> {
+    let g = d.unsigned_abs_ref().gcd(n);
+    (g != Natural::ONE && g != *n).then_some(g)
+}
+
+/// `(iteration, a, p, q, elapsed)` of a held back [`Progress`].
+type Held = (Integer, Integer, Integer, Integer, Duration);
+
+/// Passes updates on to `inner`, but holds back `found` until [`ratio_fermat`] has checked
+/// that the hit on `4uvn` gives a proper split of `n`.
+struct HoldFound<'a> {
+    inner: &'a dyn ProgressSink,
+    found: RefCell<Option<Held>>,
+}
+
+impl HoldFound<'_> {
+    /// Reports the held hit to `inner`.
+    fn release(&self) {
+        if let Some((iteration, a, p, q, elapsed)) = self.found.take() {
+            self.inner.found(&Progress {
+                iteration: &iteration,
+                a: &a,
+                p: &p,
+                q: &q,
+                elapsed,
+            });
+        }
+    }
+}
+
+impl ProgressSink for HoldFound<'_> {
+    fn update(&self, progress: &Progress) {
+        self.inner.update(progress);
+    }
+
+    fn found(&self, progress: &Progress) {
+        *self.found.borrow_mut() = Some((
+            progress.iteration.clone(),
+            progress.a.clone(),
+            progress.p.clone(),
+            progress.q.clone(),
+            progress.elapsed,
+        ));
+    }
+}
+
+/// Attempts to factor `n` using Fermat's method on `4·u·v·n`.
+///
+/// If `p ≈ (u/v)·q`, then `4uvn = (vp + uq)² − (vp − uq)²` with a small difference,
+/// so [`difference_of_squares`](crate::factor::difference_of_squares) on `4uvn` succeeds quickly and `gcd(a − b, n)` recovers
+/// a factor. Splits of `4uvn` that only separate the multiplier are skipped and the
+/// search resumes at the next `a`.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `ratio` - The expected ratio `u/v` between the factors.
+/// * `iteration` - A mutable counter tracking the Fermat iteration on `4uvn`, as in [`difference_of_squares`](crate::factor::difference_of_squares).
+/// * `cancel` - Stops the search once cancelled, leaving `iteration` at the first iteration not run.
+/// * `progress` - Receives the progress of the Fermat search on `4uvn`. Only the hit that
+///   splits `n` is passed to [`found`](ProgressSink::found).
+///
+/// # Returns
+/// `Some((p, q))` with `p ≤ q` if a non-trivial factor pair is found, otherwise `None`.
+pub fn ratio_fermat(
+    n: &Integer,
+    ratio: Ratio,
+    iteration: &mut Integer,
+    cancel: &CancelToken,
+    progress: &dyn ProgressSink,
+) -> Option<(Integer, Integer)> {
+    if *n < 4 || is_probable_prime(n) {
+        return None;
+    }
+    let n_nat = n.unsigned_abs_ref();
+    let multiple = n * ratio.multiplier();
+    let limit = SearchLimit::default();
+    let held = HoldFound {
+        inner: progress,
+        found: RefCell::new(None),
+    };
+
+    loop {
+        let FermatOutcome::Found(a_minus_b, _) =
+            bounded_difference_of_squares(&multiple, iteration, &limit, cancel, &held)
+        else {
+            return None;
+        };
+        if let Some(g) = proper_gcd(&a_minus_b, n_nat) {
+            held.release();
+            return Some(pair(g, n_nat));
+        }
+        *iteration += Integer::ONE;
+    }
+}
+
+/// Attempts to factor `n` by running multiplier Fermat for every ratio from [`ratios_up_to`]
+/// side by side.
+///
+/// Each round advances the Fermat search on `4uvn` by one step for every ratio, so the
+/// total work is about the number of ratios times the steps needed by the best one.
+/// The ratio `1/1` is plain Fermat, which guarantees termination for odd composites.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `k` - Largest numerator and denominator tried.
+/// * `iteration` - A mutable counter tracking the number of rounds.
+/// * `cancel` - Checked every [`CANCEL_ROUNDS`](crate::hart::CANCEL_ROUNDS) rounds; stops the search once cancelled.
+///
+/// # Returns
+/// `Some((p, q))` with `p ≤ q` if a non-trivial factor pair is found, otherwise `None`.
+pub fn auto_ratio_fermat(
+    n: &Integer,
+    k: u64,
+    iteration: &mut Integer,
+    cancel: &CancelToken,
+) -> Option<(Integer, Integer)> {
+    if *n < 4 || is_probable_prime(n) {
+        return None;
+    }
+    let n_nat = n.unsigned_abs_ref();
+
+    // (4uvn, a, a² − 4uvn) for every ratio
+    let mut searches: Vec<(Integer, Integer, Integer)> = ratios_up_to(k.max(1))
+        .into_iter()
+        .map(|r| {
+            let multiple = n * r.multiplier();
+            let a = sqrt_ceil(&multiple);
+            let x2 = (&a).square() - &multiple;
+            (multiple, a, x2)
+        })
+        .collect();
+
+    for round in 0u64.. {
+        if round.is_multiple_of(CANCEL_ROUNDS) && cancel.is_cancelled() {
+            return None;
+        }
+        *iteration += Integer::ONE;
+        for (multiple, a, x2) in searches.iter_mut() {
+            if is_probably_square(x2) {
+                let (is_square, b) = sqrt_exact(x2);
+                if is_square {
+                    if let Some(g) = proper_gcd(&(&*a - b), n_nat) {
+                        return Some(pair(g, n_nat));
+                    }
+                }
+            }
+            *x2 += Integer::from(2) * &*a + Integer::ONE;
+            *a += Integer::ONE;
+            if *a > *multiple {
+                return None;
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,105 @@
+use crate::cancel::CancelToken;
+use crate::prime::is_probable_prime;
+use malachite::base::num::arithmetic::traits::{AbsDiff, Gcd, ModAdd, ModMul, ModSquare, Parity};
+use malachite::base::num::basic::traits::One;
+use malachite::{Integer, Natural};
+
+/// Number of `|x - y|` products accumulated before each GCD.
+pub const GCD_BATCH: u64 = 128;
+
+/// Number of polynomial constants `c` tried before giving up.
+pub const MAX_ATTEMPTS: u64 = 32;
+
+/// Attempts to factor `n` using Pollard's rho method with Brent's cycle finding.
+///
+/// Iterates `f(x) = x² + c (mod n)` and multiplies `GCD_BATCH` differences together
+/// before taking a single GCD with `n`. If a batch overshoots to `n`, the batch is
+/// replayed one step at a time; if that still yields `n`, the next constant `c` is tried.
+///
+/// # Arguments
+/// * `n` - The number to factor.
+/// * `seed` - Starting value `x₀`; the constants tried are `c = seed + 1, seed + 2, ...`.
+/// * `iteration` - A mutable counter tracking the number of polynomial evaluations.
+/// * `cancel` - Checked before each GCD batch; stops the search once cancelled.
+///
+/// # Returns
+/// `Some((p, q))` with `p ≤ q` if a non-trivial factor pair is found, otherwise `None`.
+pub fn pollard_rho(
+    n: &Integer,
+    seed: u64,
+    iteration: &mut Integer,
+    cancel: &CancelToken,
+) -> Option<(Integer, Integer)> {
+    if *n < 4 || is_probable_prime(n) {
+        return None;
+    }
+    let n = n.unsigned_abs_ref();
+    if n.even() {
+        return Some((Integer::from(2), Integer::from(n >> 1)));
+    }
+
+    for attempt in 0..MAX_ATTEMPTS {
+        let c = Natural::from(seed) + Natural::from(attempt + 1);
+        let c = c % n;
+        let f = |x: &Natural| x.mod_square(n).mod_add(&c, n);
+
+        let mut y = Natural::from(seed) % n;
+        let mut x = y.clone();
+        let mut ys = y.clone();
+        let mut g = Natural::ONE;
+        let mut q = Natural::ONE;
+        let mut r = 1u64;
+
+        while g == Natural::ONE {
+            x = y.clone();
+            for i in 0..r {
+                if i.is_multiple_of(GCD_BATCH) && cancel.is_cancelled() {
+                    return None;
+                }
+                y = f(&y);
+            }
+            *iteration += Integer::from(r);
+
+            let mut k = 0;
+            while k < r && g == Natural::ONE {
+                if cancel.is_cancelled() {
+                    return None;
+                }
+                ys = y.clone();
+                let steps = GCD_BATCH.min(r - k);
+                for _ in 0..steps {
+                    y = f(&y);
+                    q = q.mod_mul((&x).abs_diff(&y), n);
+                }
+                *iteration += Integer::from(steps);
+                g = (&q).gcd(n);
+                k += GCD_BATCH;
+            }
+            r *= 2;
+        }
+
+        if g == *n {
+            // The batch overshot: replay it one step at a time.
+            loop {
+                ys = f(&ys);
+                *iteration += Integer::ONE;
+                g = (&x).abs_diff(&ys).gcd(n);
+                if g != Natural::ONE {
+                    break;
+                }
+            }
+        }
+
+        if g != *n {
+            let cofactor = n / &g;
+            let (p, q) = if g <= cofactor {
+                (g, cofactor)
+            } else {
+                (cofactor, g)
+            };
+            return Some((Integer::from(p), Integer::from(q)));
+        }
+    }
+
+    None
+}
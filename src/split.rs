@@ -0,0 +1,294 @@
+use crate::cancel::CancelToken;
+use crate::ecm::ecm;
+use crate::factor::{
+    bounded_difference_of_squares, bounded_parallel_difference_of_squares, FermatOutcome,
+    SearchLimit, Searched,
+};
+use crate::hart::hart;
+use crate::pm1::pollard_pm1;
+use crate::pp1::williams_pp1;
+use crate::prime::is_probable_prime;
+use crate::progress::ProgressSink;
+use crate::qs::quadratic_sieve;
+use crate::ratio::{auto_ratio_fermat, ratio_fermat, Ratio};
+use crate::rho::pollard_rho;
+use malachite::base::num::basic::traits::{One, Zero};
+use malachite::Integer;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A splitting method with its parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    /// Fermat's difference of squares, on all threads of the current rayon pool.
+    Fermat,
+    /// Fermat on `4·u·v·n` for factors with `p ≈ (u/v)·q`.
+    RatioFermat(Ratio),
+    /// Fermat on `4·u·v·n` for every ratio with `u, v ≤ k` side by side.
+    AutoRatioFermat(u64),
+    /// Hart's one-line factoring raced against Fermat.
+    Hart,
+    /// Pollard's rho with the given seed.
+    Rho { seed: u64 },
+    /// Pollard's p−1 with stage bounds `b1` and `b2`.
+    Pm1 { b1: u64, b2: u64 },
+    /// Williams' p+1 with stage 1 bound `b1`.
+    Pp1 { b1: u64 },
+    /// Lenstra's elliptic curve method.
+    Ecm {
+        b1: u64,
+        b2: u64,
+        curves: u64,
+        seed: u64,
+    },
+    /// Self-initializing quadratic sieve.
+    Qs,
+}
+
+impl Method {
+    /// Short lowercase name, as printed in JSON and CSV output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Method::Fermat => "fermat",
+            Method::RatioFermat(_) => "ratio-fermat",
+            Method::AutoRatioFermat(_) => "auto-ratio-fermat",
+            Method::Hart => "hart",
+            Method::Rho { .. } => "rho",
+            Method::Pm1 { .. } => "pm1",
+            Method::Pp1 { .. } => "pp1",
+            Method::Ecm { .. } => "ecm",
+            Method::Qs => "qs",
+        }
+    }
+
+    /// Whether the method is a difference of squares search counting iterations like
+    /// [`difference_of_squares`](crate::factor::difference_of_squares), so that a start
+    /// offset applies to it.
+    pub fn is_fermat(&self) -> bool {
+        matches!(self, Method::Fermat | Method::RatioFermat(_))
+    }
+}
+
+/// Why a run ended without a split.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// `n` is below `4`, so there is nothing to split.
+    TooSmall,
+    /// `n` is a probable prime.
+    Prime,
+    /// `n ≡ 2 (mod 4)` is not a difference of two squares, so Fermat-type methods cannot
+    /// split it.
+    EvenInput,
+    /// The only split the search reached was the trivial `1 · n`.
+    TrivialSplit,
+    /// The [`SearchLimit`] stopped the search, with the `a` values it ruled out.
+    Exhausted { searched: Searched },
+    /// The [`CancelToken`] was cancelled. Fermat searches report the `a` values they
+    /// ruled out.
+    Cancelled { searched: Option<Searched> },
+    /// The [`CancelToken`] deadline passed. Fermat searches report the `a` values they
+    /// ruled out.
+    TimedOut { searched: Option<Searched> },
+    /// The method used up its bounds, curves or attempts without a factor.
+    NotFound,
+}
+
+impl Failure {
+    /// Short machine-readable code, as printed in JSON and CSV output.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Failure::TooSmall => "too_small",
+            Failure::Prime => "prime",
+            Failure::EvenInput => "even_input",
+            Failure::TrivialSplit => "trivial_split",
+            Failure::Exhausted { .. } => "exhausted",
+            Failure::Cancelled { .. } => "cancelled",
+            Failure::TimedOut { .. } => "timed_out",
+            Failure::NotFound => "not_found",
+        }
+    }
+
+    /// The `a` values ruled out, for Fermat searches that stopped early.
+    pub fn searched(&self) -> Option<&Searched> {
+        match self {
+            Failure::Exhausted { searched } => Some(searched),
+            Failure::Cancelled { searched } | Failure::TimedOut { searched } => searched.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The proven lower bound on `q − p`, for Fermat searches that stopped early after
+    /// covering every `a` from `⌈√n⌉`.
+    pub fn min_gap(&self) -> Option<&Integer> {
+        self.searched()?.min_gap.as_ref()
+    }
+
+    /// The failure of a search for `n` that resumed a run which began at `a = start`: the
+    /// searched range reaches back to `start`, with the `q − p` bound that earns.
+    pub fn resumed_from(self, n: &Integer, start: &Integer) -> Self {
+        let resume = |searched: Searched| Searched::new(n, start.clone(), searched.end);
+        match self {
+            Failure::Exhausted { searched } => Failure::Exhausted {
+                searched: resume(searched),
+            },
+            Failure::Cancelled { searched } => Failure::Cancelled {
+                searched: searched.map(resume),
+            },
+            Failure::TimedOut { searched } => Failure::TimedOut {
+                searched: searched.map(resume),
+            },
+            failure => failure,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::TooSmall => write!(f, "input is below 4"),
+            Failure::Prime => write!(f, "input is prime"),
+            Failure::EvenInput => write!(f, "input is 2 mod 4, not a difference of squares"),
+            Failure::TrivialSplit => write!(f, "only the trivial split 1 · n was found"),
+            Failure::Exhausted { searched } => match &searched.min_gap {
+                Some(min_gap) => write!(f, "search limit reached, |p − q| ≥ {}", min_gap),
+                None => write!(
+                    f,
+                    "search limit reached, searched a ∈ [{}, {})",
+                    searched.start, searched.end
+                ),
+            },
+            Failure::Cancelled { .. } => write!(f, "cancelled"),
+            Failure::TimedOut { .. } => write!(f, "timed out"),
+            Failure::NotFound => write!(f, "no factor found within the method's bounds"),
+        }
+    }
+}
+
+/// The report of one [`split`] run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FactorOutcome {
+    /// The factor pair `p ≤ q`, or why there is none.
+    pub result: Result<(Integer, Integer), Failure>,
+    /// The method that ran.
+    pub method: Method,
+    /// The method's iteration count when it stopped. For Fermat methods that did not
+    /// find a factor, this is the first iteration not run, where a search can resume.
+    pub iterations: Integer,
+    /// Wall time of the run.
+    pub elapsed: Duration,
+}
+
+impl FactorOutcome {
+    /// The factor pair, if one was found.
+    pub fn found(self) -> Option<(Integer, Integer)> {
+        self.result.ok()
+    }
+}
+
+/// Splits `n` once with `method` and reports how it went.
+///
+/// Primes, inputs below 4, and inputs `≡ 2 (mod 4)` for Fermat-type methods are
+/// rejected up front. Every method runs under `cancel`; `limit` and `progress` apply to
+/// the Fermat methods that support them.
+///
+/// # Arguments
+/// * `n` - The number to split.
+/// * `method` - The method and its parameters.
+/// * `start` - First Fermat iteration, as in [`difference_of_squares`](crate::factor::difference_of_squares). Other methods count from zero.
+/// * `limit` - Iteration and gap limits for [`Method::Fermat`].
+/// * `cancel` - Stops the run once cancelled or past its deadline.
+/// * `progress` - Receives progress from [`Method::Fermat`] and [`Method::RatioFermat`].
+///
+/// # Returns
+/// A [`FactorOutcome`] with the factors or a [`Failure`], the iteration count and the elapsed time.
+pub fn split(
+    n: &Integer,
+    method: Method,
+    start: &Integer,
+    limit: &SearchLimit,
+    cancel: &CancelToken,
+    progress: &dyn ProgressSink,
+) -> FactorOutcome {
+    let started = Instant::now();
+    let mut iteration = if method.is_fermat() {
+        start.clone()
+    } else {
+        Integer::ZERO
+    };
+    let result = run(n, method, &mut iteration, limit, cancel, progress);
+    FactorOutcome {
+        result,
+        method,
+        iterations: iteration,
+        elapsed: started.elapsed(),
+    }
+}
+
+fn run(
+    n: &Integer,
+    method: Method,
+    iteration: &mut Integer,
+    limit: &SearchLimit,
+    cancel: &CancelToken,
+    progress: &dyn ProgressSink,
+) -> Result<(Integer, Integer), Failure> {
+    if *n < 4 {
+        return Err(if *n > Integer::ONE {
+            Failure::Prime
+        } else {
+            Failure::TooSmall
+        });
+    }
+    if is_probable_prime(n) {
+        return Err(Failure::Prime);
+    }
+    let fermat_type = matches!(
+        method,
+        Method::Fermat | Method::RatioFermat(_) | Method::AutoRatioFermat(_) | Method::Hart
+    );
+    if fermat_type && (n % Integer::from(4)) == 2 {
+        return Err(Failure::EvenInput);
+    }
+
+    let stopped = |searched: Option<Searched>| {
+        if cancel.timed_out() {
+            Failure::TimedOut { searched }
+        } else {
+            Failure::Cancelled { searched }
+        }
+    };
+    let pair = match method {
+        Method::Fermat => {
+            let outcome = if rayon::current_num_threads() > 1 {
+                bounded_parallel_difference_of_squares(n, iteration, limit, cancel, progress)
+            } else {
+                bounded_difference_of_squares(n, iteration, limit, cancel, progress)
+            };
+            return match outcome {
+                FermatOutcome::Found(p, q) => Ok((p, q)),
+                FermatOutcome::Exhausted { searched } => Err(Failure::Exhausted { searched }),
+                FermatOutcome::Cancelled { searched } => Err(stopped(Some(searched))),
+                FermatOutcome::NoFactor => Err(Failure::TrivialSplit),
+            };
+        }
+        Method::RatioFermat(ratio) => ratio_fermat(n, ratio, iteration, cancel, progress),
+        Method::AutoRatioFermat(k) => auto_ratio_fermat(n, k, iteration, cancel),
+        Method::Hart => hart(n, iteration, cancel),
+        Method::Rho { seed } => pollard_rho(n, seed, iteration, cancel),
+        Method::Pm1 { b1, b2 } => pollard_pm1(n, b1, b2, iteration, cancel),
+        Method::Pp1 { b1 } => williams_pp1(n, b1, iteration, cancel),
+        Method::Ecm {
+            b1,
+            b2,
+            curves,
+            seed,
+        } => ecm(n, b1, b2, curves, seed, iteration, cancel),
+        Method::Qs => quadratic_sieve(n, iteration, cancel),
+    };
+    match pair {
+        Some(pair) => Ok(pair),
+        None if cancel.is_cancelled() => Err(stopped(None)),
+        None if fermat_type => Err(Failure::TrivialSplit),
+        None => Err(Failure::NotFound),
+    }
+}
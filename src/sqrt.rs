@@ -1,26 +1,55 @@
-use crate::legendre::LEGENDRE_TABLES;
-use malachite::base::num::arithmetic::traits::{CeilingSqrt, FloorSqrt, Square};
+use crate::backend::Backend;
+use crate::legendre::{LEGENDRE_TABLES, QR_11, QR_63, QR_64, QR_65, QR_ODD_MODULUS};
 use malachite::base::num::conversion::traits::WrappingFrom;
 use malachite::Integer;
 
 /// Check if `n` is a perfect square and return a tuple `(is_square, sqrt)`.
 ///
 /// If `is_square` is `true`, `sqrt` is the exact root.
-/// Otherwise, `sqrt` is the floor of the root.
-pub fn sqrt_exact(n: &Integer) -> (bool, Integer) {
+/// Otherwise, `sqrt` is the floor of the root. Works on any [`Backend`].
+pub fn sqrt_exact<T: Backend>(n: &T) -> (bool, T) {
     let root = n.floor_sqrt();
-    let is_square = root.clone().square() == *n;
+    let is_square = root.square() == *n;
     (is_square, root)
 }
 
-/// Return the smallest integer ≥ sqrt(n).
-pub fn sqrt_ceil(n: &Integer) -> Integer {
-    n.ceiling_sqrt()
+/// Return the smallest integer ≥ sqrt(n), for any [`Backend`].
+pub fn sqrt_ceil<T: Backend>(n: &T) -> T {
+    let (is_square, mut root) = sqrt_exact(n);
+    if !is_square {
+        root.add_u64(1);
+    }
+    root
 }
 
 /// Fast heuristic check if `n` could be a square using Legendre tables.
 /// Helps skip obvious non-squares quickly in factorization algorithms.
+///
+/// The packed tables modulo 64, 63, 65 and 11 go first, as in GMP's
+/// `mpz_perfect_square_p`: the low limb and a single remainder by `63 · 65 · 11` reject
+/// over 99% of non-squares. The Legendre tables for the primes from 17 up catch most of
+/// the rest.
 pub fn is_probably_square(n: &Integer) -> bool {
+    if *n < 0 {
+        return false;
+    }
+    if !QR_64.contains(u64::wrapping_from(n)) {
+        return false;
+    }
+    let r = u64::wrapping_from(&(n % Integer::from(QR_ODD_MODULUS)));
+    if !(QR_63.contains(r) && QR_65.contains(r) && QR_11.contains(r)) {
+        return false;
+    }
+    LEGENDRE_TABLES
+        .iter()
+        .filter(|&&(p, _)| !QR_ODD_MODULUS.is_multiple_of(p))
+        .all(|&(p, table)| table[u64::wrapping_from(&(n % Integer::from(p))) as usize] != -1)
+}
+
+/// The original check against every [`LEGENDRE_TABLES`] prime, one bigint remainder each.
+///
+/// Kept as the baseline that [`is_probably_square`] is measured against.
+pub fn legendre_probably_square(n: &Integer) -> bool {
     for &(p, table) in LEGENDRE_TABLES {
         let r = u64::wrapping_from(&(n % Integer::from(p))) as usize;
         if table[r] == -1 {
@@ -29,3 +58,95 @@ pub fn is_probably_square(n: &Integer) -> bool {
     }
     true
 }
+
+/// Most moduli a [`SquareFilter`] holds: 64, 63 and every [`LEGENDRE_TABLES`] prime.
+///
+/// Every modulus is below 128, so residues and their sums fit in a byte.
+const FILTER_MODULI: usize = 2 + LEGENDRE_TABLES.len();
+
+/// Steps below this have their residues modulo every filter modulus precomputed.
+const STEP_TABLE: usize = 64;
+
+/// Screens `a² − n` for squareness using only `a mod m` for the moduli 64 and 63 and the
+/// Legendre table primes.
+///
+/// For every modulus `m`, the residues `r = a mod m` with `r² − n` a non-residue are marked
+/// once up front. The filter then follows one `a` as it moves forward, keeping `a mod m`
+/// as a small integer per modulus. A step adds the increment, reduced modulo `m` from a
+/// table, and subtracts `m` on overflow; a check is one table lookup per modulus. Neither
+/// needs a division or any bigint work.
+#[derive(Clone, Debug)]
+pub struct SquareFilter {
+    /// Number of moduli in use. The unused slots have modulus 1 and residue 0.
+    len: usize,
+    moduli: [u8; FILTER_MODULI],
+    /// `a mod m` for the current `a` and every modulus.
+    residues: [u8; FILTER_MODULI],
+    /// `steps[d][i]` is `d mod moduli[i]`.
+    steps: Vec<[u8; FILTER_MODULI]>,
+    /// Start of the table of modulus `i` in `admissible`.
+    tables: [u32; FILTER_MODULI],
+    /// `admissible[tables[i] + r]` is `true` if `r² − n` can be a square modulo `moduli[i]`.
+    admissible: Vec<bool>,
+}
+
+impl SquareFilter {
+    /// Builds the filter for `n`, positioned at `a = a0`, leaving out the first `skip`
+    /// primes of [`LEGENDRE_TABLES`] (for example those already covered by a wheel).
+    pub fn new(n: &Integer, a0: &Integer, skip: usize) -> Self {
+        let residue = |x: &Integer, m: u64| u64::wrapping_from(&(x % Integer::from(m)));
+        let mut filter = SquareFilter {
+            len: 0,
+            moduli: [1; FILTER_MODULI],
+            residues: [0; FILTER_MODULI],
+            steps: Vec::new(),
+            tables: [0; FILTER_MODULI],
+            admissible: Vec::new(),
+        };
+        let mut add = |m: u64, is_square: &dyn Fn(u64) -> bool| {
+            let nm = residue(n, m);
+            filter.moduli[filter.len] = m as u8;
+            filter.residues[filter.len] = residue(a0, m) as u8;
+            filter.tables[filter.len] = filter.admissible.len() as u32;
+            filter
+                .admissible
+                .extend((0..m).map(|r| is_square((r * r % m + m - nm) % m)));
+            filter.len += 1;
+        };
+
+        // The 2-adic and mod 9 conditions are not covered by any Legendre prime.
+        add(QR_64.modulus, &|r| QR_64.contains(r));
+        add(QR_63.modulus, &|r| QR_63.contains(r));
+        for &(p, table) in &LEGENDRE_TABLES[skip.min(LEGENDRE_TABLES.len())..] {
+            add(p, &|r| table[r as usize] != -1);
+        }
+        filter.steps = (0..STEP_TABLE)
+            .map(|d| filter.moduli.map(|m| (d % m as usize) as u8))
+            .collect();
+        filter
+    }
+
+    /// Moves the filter from `a` to `a + step`.
+    ///
+    /// Steps below [`STEP_TABLE`], which covers almost every increment of a residue wheel,
+    /// cost one addition and one conditional subtraction per modulus.
+    pub fn advance(&mut self, step: u64) {
+        let reduced = if step < STEP_TABLE as u64 {
+            self.steps[step as usize]
+        } else {
+            self.moduli.map(|m| (step % m as u64) as u8)
+        };
+        for ((r, &m), d) in self.residues.iter_mut().zip(&self.moduli).zip(reduced) {
+            let sum = *r + d;
+            *r = sum.min(sum.wrapping_sub(m));
+        }
+    }
+
+    /// Returns `false` if `a² − n` is certainly not a square for the current `a`.
+    pub fn passes(&self) -> bool {
+        self.residues[..self.len]
+            .iter()
+            .zip(&self.tables)
+            .all(|(&r, &table)| self.admissible[table as usize + r as usize])
+    }
+}
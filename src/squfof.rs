@@ -0,0 +1,134 @@
+/// Largest input size, in bits, that [`squfof`] accepts.
+pub const MAX_BITS: u32 = 62;
+
+/// Squarefree multipliers `k` tried in turn; each run works on the form of discriminant `kN`.
+pub const MULTIPLIERS: &[u64] = &[
+    1,
+    3,
+    5,
+    7,
+    11,
+    3 * 5,
+    3 * 7,
+    3 * 11,
+    5 * 7,
+    5 * 11,
+    7 * 11,
+    3 * 5 * 7,
+    3 * 5 * 11,
+    3 * 7 * 11,
+    5 * 7 * 11,
+    3 * 5 * 7 * 11,
+];
+
+/// Floor of the square root of `n`.
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = (n as f64).sqrt() as u128;
+    while x * x > n {
+        x -= 1;
+    }
+    while (x + 1) * (x + 1) <= n {
+        x += 1;
+    }
+    x
+}
+
+/// Greatest common divisor on machine words.
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Runs SQUFOF once on the discriminant `kn`, returning a non-trivial factor of `n`.
+fn squfof_with(n: u64, kn: u128) -> Option<u64> {
+    let p0 = isqrt(kn);
+    if p0 * p0 == kn {
+        let g = gcd(n, p0 as u64);
+        return (g != 1 && g != n).then_some(g);
+    }
+
+    // Forward cycle: look for a square Q at an even index.
+    let bound = 3 * 2 * isqrt(2 * isqrt(kn));
+    let (mut p_prev, mut p) = (p0, p0);
+    let (mut q_prev, mut q) = (1u128, kn - p0 * p0);
+    let mut root = 0;
+    for i in 2..bound {
+        let b = (p0 + p) / q;
+        p = b * q - p;
+        let q_next = (q_prev as i128 + b as i128 * (p_prev as i128 - p as i128)) as u128;
+        q_prev = q;
+        q = q_next;
+        p_prev = p;
+        let r = isqrt(q);
+        if i % 2 == 0 && r * r == q {
+            root = r;
+            break;
+        }
+    }
+    if root == 0 {
+        return None;
+    }
+
+    // Reverse cycle from the square root form until P repeats.
+    let b = (p0 - p) / root;
+    p += b * root;
+    q_prev = root;
+    q = (kn - p * p) / q_prev;
+    loop {
+        let b = (p0 + p) / q;
+        p_prev = p;
+        p = b * q - p;
+        let q_next = (q_prev as i128 + b as i128 * (p_prev as i128 - p as i128)) as u128;
+        q_prev = q;
+        q = q_next;
+        if p == p_prev {
+            break;
+        }
+    }
+
+    let g = gcd(n, p as u64);
+    (g != 1 && g != n).then_some(g)
+}
+
+/// Attempts to find a factor of `n` with Shanks' square forms factorization (SQUFOF).
+///
+/// Walks the continued fraction expansion of `√(kN)` looking for a square form, then
+/// follows the reverse cycle to an ambiguous form whose coefficient shares a factor with
+/// `n`. Each multiplier in [`MULTIPLIERS`] gets one attempt. All arithmetic stays in
+/// `u64`/`u128`, so this is far cheaper than a bignum method for word-sized cofactors.
+///
+/// # Arguments
+/// * `n` - The number to factor. Must be below `2^62`.
+///
+/// # Returns
+/// `Some(f)` with `1 < f < n` and `f | n` if a factor is found, otherwise `None`
+/// (also for primes and inputs that are too large).
+pub fn squfof(n: u64) -> Option<u64> {
+    if n < 4 || n >> MAX_BITS != 0 {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    let root = isqrt(n as u128) as u64;
+    if root * root == n {
+        return Some(root);
+    }
+    for &k in MULTIPLIERS {
+        if k != 1 && n.is_multiple_of(k) {
+            let g = gcd(n, k);
+            if g != n {
+                return Some(g);
+            }
+        }
+        if let Some(f) = squfof_with(n, k as u128 * n as u128) {
+            return Some(f);
+        }
+    }
+    None
+}
@@ -0,0 +1,76 @@
+use crate::legendre::LEGENDRE_TABLES;
+use malachite::base::num::conversion::traits::WrappingFrom;
+use malachite::Integer;
+
+/// Number of [`LEGENDRE_TABLES`] primes in the default wheel: `M = 3·5·7·11·13 = 15015`.
+pub const WHEEL_PRIMES: usize = 5;
+
+/// The residues of `a` modulo `M` for which `a² − n` can be a square.
+///
+/// For every prime `p | M`, `a² − n` must be a quadratic residue (or zero) modulo `p`.
+/// Roughly `(p + 1) / 2p` of the residues survive each prime, so the default wheel keeps
+/// under 7% of all `a` values.
+#[derive(Clone, Debug)]
+pub struct Wheel {
+    /// The wheel modulus `M`.
+    pub modulus: u64,
+    /// Admissible residues of `a mod M`, in increasing order.
+    pub residues: Vec<u64>,
+    /// `increments[i]` is the distance from `residues[i]` to the next admissible residue.
+    pub increments: Vec<u64>,
+}
+
+impl Wheel {
+    /// Builds the wheel for `n` from the first `primes` entries of [`LEGENDRE_TABLES`].
+    pub fn new(n: &Integer, primes: usize) -> Self {
+        let tables = &LEGENDRE_TABLES[..primes.min(LEGENDRE_TABLES.len())];
+        let modulus: u64 = tables.iter().map(|&(p, _)| p).product();
+        let n_mod: Vec<u64> = tables
+            .iter()
+            .map(|&(p, _)| u64::wrapping_from(&(n % Integer::from(p))))
+            .collect();
+
+        let residues: Vec<u64> = (0..modulus)
+            .filter(|&r| {
+                tables.iter().zip(&n_mod).all(|(&(p, table), &np)| {
+                    let rp = r % p;
+                    table[((rp * rp + p - np) % p) as usize] != -1
+                })
+            })
+            .collect();
+
+        let increments = residues
+            .iter()
+            .zip(residues.iter().cycle().skip(1))
+            .map(|(&r, &next)| (next + modulus - r - 1) % modulus + 1)
+            .collect();
+
+        Wheel {
+            modulus,
+            residues,
+            increments,
+        }
+    }
+
+    /// Finds the first admissible value at or after `a`.
+    ///
+    /// # Returns
+    /// `(index, skip)` where `a + skip` is admissible and `residues[index]` is its residue.
+    pub fn start(&self, a: &Integer) -> (usize, u64) {
+        let r = u64::wrapping_from(&(a % Integer::from(self.modulus)));
+        match self.residues.iter().position(|&x| x >= r) {
+            Some(index) => (index, self.residues[index] - r),
+            None => (0, self.modulus - r + self.residues[0]),
+        }
+    }
+
+    /// The increment to the next admissible value, advancing `index` along the wheel.
+    pub fn advance(&self, index: &mut usize) -> u64 {
+        let d = self.increments[*index];
+        *index += 1;
+        if *index == self.residues.len() {
+            *index = 0;
+        }
+        d
+    }
+}
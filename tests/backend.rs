@@ -0,0 +1,170 @@
+use diffsquare::backend::{difference_of_squares, factorize, Backend};
+use diffsquare::sqrt::{sqrt_ceil, sqrt_exact};
+use malachite::{Integer, Natural};
+use std::str::FromStr;
+
+/// A machine-word backend, to check that nothing in the search needs malachite.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Word(u128);
+
+impl Backend for Word {
+    fn from_u64(x: u64) -> Self {
+        Word(x as u128)
+    }
+
+    fn to_integer(&self) -> Integer {
+        Integer::from(self.0)
+    }
+
+    fn add_assign(&mut self, other: &Self) {
+        self.0 += other.0;
+    }
+
+    fn sub_assign(&mut self, other: &Self) {
+        self.0 -= other.0;
+    }
+
+    fn add_u64(&mut self, x: u64) {
+        self.0 += x as u128;
+    }
+
+    fn square(&self) -> Self {
+        Word(self.0 * self.0)
+    }
+
+    fn floor_sqrt(&self) -> Self {
+        let mut r = (self.0 as f64).sqrt() as u128;
+        while r * r > self.0 {
+            r -= 1;
+        }
+        while (r + 1) * (r + 1) <= self.0 {
+            r += 1;
+        }
+        Word(r)
+    }
+
+    fn mod_u64(&self, m: u64) -> u64 {
+        (self.0 % m as u128) as u64
+    }
+
+    fn div_u64(&self, d: u64) -> Self {
+        Word(self.0 / d as u128)
+    }
+}
+
+#[test]
+fn test_malachite_backends() {
+    let n = Natural::from(5959u32);
+    let mut iteration = Natural::from(0u32);
+    assert_eq!(
+        difference_of_squares(&n, &mut iteration, 4, true),
+        Some((Natural::from(59u32), Natural::from(101u32)))
+    );
+    assert_eq!(iteration, 3u32);
+
+    assert_eq!(
+        sqrt_exact(&Natural::from(49u32)),
+        (true, Natural::from(7u32))
+    );
+    assert_eq!(
+        sqrt_exact(&Natural::from(50u32)),
+        (false, Natural::from(7u32))
+    );
+    assert_eq!(sqrt_ceil(&Natural::from(50u32)), Natural::from(8u32));
+    assert_eq!(sqrt_ceil(&Integer::from(49)), Integer::from(7));
+
+    let mut iterations = Natural::from(0u32);
+    assert_eq!(
+        factorize(&Natural::from(360u32), &mut iterations, 4, true),
+        vec![
+            (Natural::from(2u32), 3),
+            (Natural::from(3u32), 2),
+            (Natural::from(5u32), 1)
+        ]
+    );
+
+    let x = Integer::from_str("-123456789012345678901234567890").unwrap();
+    assert_eq!(x.to_integer(), x);
+}
+
+#[test]
+fn test_generic_search_matches_factor() {
+    // The generic loop visits the same a values as the malachite search
+    for (n, start) in [(5959u64, 0u64), (5959, 2), (10403, 1), (1000036000099, 1)] {
+        let mut expected_iteration = Integer::from(start);
+        let expected = diffsquare::factor::difference_of_squares(
+            &Integer::from(n),
+            &mut expected_iteration,
+            4,
+            true,
+        );
+
+        let mut iteration = Word(start as u128);
+        let found = difference_of_squares(&Word(n as u128), &mut iteration, 4, true);
+        assert_eq!(
+            found.map(|(p, q)| (p.to_integer(), q.to_integer())),
+            expected,
+            "{n}"
+        );
+        assert_eq!(iteration.to_integer(), expected_iteration, "{n}");
+    }
+
+    let mut iteration = Word(0);
+    assert_eq!(
+        difference_of_squares(&Word(1000003), &mut iteration, 4, true),
+        None
+    );
+
+    let mut iterations = Word(0);
+    assert_eq!(
+        factorize(&Word(24 * 1000003 * 1000033), &mut iterations, 4, true),
+        vec![
+            (Word(2), 3),
+            (Word(3), 1),
+            (Word(1000003), 1),
+            (Word(1000033), 1)
+        ]
+    );
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn test_num_bigint_backends() {
+    use num_bigint::{BigInt, BigUint};
+
+    for s in [
+        "0",
+        "1",
+        "18446744073709551615",
+        "18446744073709551616",
+        "123456789012345678901234567890123",
+    ] {
+        let x = BigUint::from_str(s).unwrap();
+        assert_eq!(x.to_integer(), Integer::from_str(s).unwrap());
+        let y = -BigInt::from_str(s).unwrap();
+        assert_eq!(y.to_integer(), -Integer::from_str(s).unwrap());
+        assert_eq!(
+            x.mod_u64(1000003),
+            u64::try_from(&(Integer::from_str(s).unwrap() % Integer::from(1000003))).unwrap()
+        );
+    }
+
+    let square = BigUint::from_str("1000000016000000063").unwrap() * BigUint::from(3u32);
+    assert_eq!(sqrt_exact(&(&square * &square)), (true, square.clone()));
+    assert_eq!(sqrt_ceil(&(&square * &square + 1u32)), &square + 1u32);
+    assert_eq!(sqrt_exact(&BigInt::from(50)), (false, BigInt::from(7)));
+
+    let n = BigUint::from_str("1000000016000000063").unwrap();
+    let mut iteration = BigUint::from(0u32);
+    assert_eq!(
+        difference_of_squares(&n, &mut iteration, 4, true),
+        Some((BigUint::from(1000000007u32), BigUint::from(1000000009u32)))
+    );
+
+    let n = BigInt::from_str("1000000016000000063").unwrap();
+    let mut iterations = BigInt::from(0);
+    assert_eq!(
+        factorize(&n, &mut iterations, 4, true),
+        vec![(BigInt::from(1000000007), 1), (BigInt::from(1000000009), 1)]
+    );
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::{SEMIPRIME, SEMIPRIME_ITERATION, SEMIPRIME_P, SEMIPRIME_Q};
+use diffsquare::cancel::CancelToken;
+use diffsquare::ecm::ecm;
+use diffsquare::factor::{
+    bounded_difference_of_squares, bounded_parallel_difference_of_squares, FermatOutcome,
+    SearchLimit, Searched,
+};
+use diffsquare::hart::hart;
+use diffsquare::lehman::lehman;
+use diffsquare::pm1::pollard_pm1;
+use diffsquare::pp1::williams_pp1;
+use diffsquare::progress::Silent;
+use diffsquare::qs::quadratic_sieve;
+use diffsquare::ratio::{auto_ratio_fermat, ratio_fermat, Ratio};
+use diffsquare::rho::pollard_rho;
+use malachite::Integer;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_cancel_token() {
+    let token = CancelToken::new();
+    assert!(!token.is_cancelled());
+    let clone = token.clone();
+    clone.cancel();
+    assert!(token.is_cancelled());
+
+    assert!(!CancelToken::with_timeout(Duration::from_secs(3600)).is_cancelled());
+    let expired = CancelToken::with_deadline(Instant::now());
+    assert!(expired.is_cancelled());
+    assert!(expired.clone().is_cancelled());
+}
+
+#[test]
+fn test_limited_token() {
+    let parent = CancelToken::new();
+    let child = parent.limited(Duration::ZERO);
+    assert!(child.is_cancelled() && child.timed_out());
+    assert!(!parent.is_cancelled());
+
+    let child = parent.limited(Duration::from_secs(3600));
+    assert!(!child.is_cancelled());
+    parent.cancel();
+    assert!(child.is_cancelled() && !child.timed_out());
+
+    // The parent's earlier deadline still applies
+    let expired = CancelToken::with_deadline(Instant::now());
+    assert!(expired.limited(Duration::from_secs(3600)).timed_out());
+}
+
+#[test]
+fn test_cancelled_methods_stop() {
+    let cancelled = CancelToken::new();
+    cancelled.cancel();
+    // 1000003 · 1000033 is found by every method without cancellation
+    let n = Integer::from(1000003u64 * 1000033);
+    let mut iter = Integer::from(0);
+
+    assert_eq!(pollard_rho(&n, 2, &mut iter, &cancelled), None);
+    assert_eq!(pollard_pm1(&n, 1000, 50_000, &mut iter, &cancelled), None);
+    assert_eq!(williams_pp1(&n, 1000, &mut iter, &cancelled), None);
+    assert_eq!(ecm(&n, 2000, 100_000, 8, 0, &mut iter, &cancelled), None);
+    assert_eq!(quadratic_sieve(&n, &mut iter, &cancelled), None);
+    assert_eq!(hart(&n, &mut iter, &cancelled), None);
+    assert_eq!(lehman(&n, &mut iter, &cancelled), None);
+    assert_eq!(auto_ratio_fermat(&n, 3, &mut iter, &cancelled), None);
+    assert_eq!(iter, 0);
+
+    let ratio = Ratio { u: 1, v: 1 };
+    let mut iter = Integer::from(1);
+    assert_eq!(
+        ratio_fermat(&n, ratio, &mut iter, &cancelled, &Silent),
+        None
+    );
+    assert_eq!(iter, 1);
+}
+
+#[test]
+fn test_cancelled_fermat_reports_progress() {
+    let limit = SearchLimit::default();
+    let cancelled = CancelToken::new();
+    cancelled.cancel();
+
+    // Nothing has run, so only the bound at ⌈√n⌉ is known: 2·⌈√(78² − 5959)⌉ = 2·⌈√125⌉.
+    let n = Integer::from(5959);
+    let mut iter = Integer::from(1);
+    assert_eq!(
+        bounded_difference_of_squares(&n, &mut iter, &limit, &cancelled, &Silent),
+        FermatOutcome::Cancelled {
+            searched: Searched {
+                start: Integer::from(78),
+                end: Integer::from(78),
+                min_gap: Some(Integer::from(24))
+            }
+        }
+    );
+    assert_eq!(iter, 1);
+
+    // p = 2^40 + 15 and q = 2^80 + 13 are far too far apart for Fermat.
+    let p = (Integer::from(1) << 40u32) + Integer::from(15);
+    let q = (Integer::from(1) << 80u32) + Integer::from(13);
+    let n = &p * &q;
+    for parallel in [false, true] {
+        let token = CancelToken::with_timeout(Duration::from_millis(50));
+        let started = Instant::now();
+        let mut iter = Integer::from(1);
+        let outcome = if parallel {
+            bounded_parallel_difference_of_squares(&n, &mut iter, &limit, &token, &Silent)
+        } else {
+            bounded_difference_of_squares(&n, &mut iter, &limit, &token, &Silent)
+        };
+        assert!(started.elapsed() < Duration::from_secs(10));
+        let FermatOutcome::Cancelled { searched } = outcome else {
+            panic!("expected cancellation, got {outcome:?}");
+        };
+        assert!(iter > 1);
+        assert!(searched.min_gap.unwrap() <= &q - &p);
+    }
+}
+
+#[test]
+fn test_cancelled_fermat_resumes() {
+    let n = Integer::from(SEMIPRIME);
+    let expected = FermatOutcome::Found(Integer::from(SEMIPRIME_P), Integer::from(SEMIPRIME_Q));
+    let limit = SearchLimit::default();
+
+    for parallel in [false, true] {
+        let mut iter = Integer::from(1);
+        let search = |iter: &mut Integer, token: &CancelToken| {
+            if parallel {
+                bounded_parallel_difference_of_squares(&n, iter, &limit, token, &Silent)
+            } else {
+                bounded_difference_of_squares(&n, iter, &limit, token, &Silent)
+            }
+        };
+        let mut outcome = search(
+            &mut iter,
+            &CancelToken::with_timeout(Duration::from_millis(2)),
+        );
+        if let FermatOutcome::Cancelled { searched } = &outcome {
+            assert!(*searched.min_gap.as_ref().unwrap() <= SEMIPRIME_Q - SEMIPRIME_P);
+            assert!(iter <= SEMIPRIME_ITERATION);
+            outcome = search(&mut iter, &CancelToken::new());
+        }
+        assert_eq!(outcome, expected);
+        assert_eq!(iter, SEMIPRIME_ITERATION);
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::{SEMIPRIME, SEMIPRIME_ITERATION, SEMIPRIME_P, SEMIPRIME_Q};
+use diffsquare::cancel::CancelToken;
+use diffsquare::checkpoint::{Checkpoint, CheckpointSink};
+use diffsquare::factor::{
+    bounded_difference_of_squares, FermatOutcome, SearchLimit, PROGRESS_INTERVAL,
+};
+use diffsquare::fermat::FermatSearch;
+use diffsquare::progress::{Progress, ProgressSink};
+use diffsquare::sqrt::sqrt_ceil;
+use malachite::Integer;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// A fresh path in the temporary directory for this test.
+fn temp_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("diffsquare-{}-{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Counts the updates passed through.
+#[derive(Default)]
+struct Counter(AtomicU64);
+
+impl ProgressSink for Counter {
+    fn update(&self, _progress: &Progress) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn test_checkpoint_round_trip() {
+    let path = temp_path("round-trip");
+    let checkpoint = Checkpoint::new(
+        &Integer::from(SEMIPRIME),
+        &Integer::from(1599072387),
+        &Integer::from(1000),
+        &Integer::from(1),
+        "ratio-fermat 3/2",
+    );
+    assert!(checkpoint.timestamp > 0);
+    checkpoint.save(&path).unwrap();
+    assert_eq!(Checkpoint::load(&path).unwrap(), checkpoint);
+
+    // Without `first`, the saved run is taken to have begun where it stands
+    std::fs::write(
+        &path,
+        r#"{"n": "5959", "a": "80", "iteration": "3", "method": "fermat", "timestamp": 0}"#,
+    )
+    .unwrap();
+    assert_eq!(Checkpoint::load(&path).unwrap().first, 3);
+
+    std::fs::write(
+        &path,
+        r#"{"n": "12x", "a": "1", "iteration": "1", "method": "fermat", "timestamp": 0}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        Checkpoint::load(&path).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    std::fs::write(&path, "not a checkpoint").unwrap();
+    assert_eq!(
+        Checkpoint::load(&path).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_checkpoint_sink_resumes() {
+    let n = Integer::from(SEMIPRIME);
+    let path = temp_path("sink");
+    let counter = Counter::default();
+    let sink = CheckpointSink::new(
+        &path,
+        Duration::ZERO,
+        &n,
+        &Integer::from(1),
+        "fermat",
+        &counter,
+    );
+    let limit = SearchLimit {
+        max_iterations: Some(Integer::from(5 * PROGRESS_INTERVAL)),
+        max_gap_bits: None,
+    };
+    let mut iter = Integer::from(1);
+    let outcome = bounded_difference_of_squares(&n, &mut iter, &limit, &CancelToken::new(), &sink);
+    assert!(matches!(outcome, FermatOutcome::Exhausted { .. }));
+    assert_eq!(counter.0.load(Ordering::Relaxed), 5);
+
+    // The last update is saved, early in the fifth million
+    let checkpoint = Checkpoint::load(&path).unwrap();
+    assert_eq!(checkpoint.n, n);
+    assert_eq!(checkpoint.method, "fermat");
+    assert_eq!(checkpoint.first, 1);
+    assert_eq!(checkpoint.state().start, sqrt_ceil(&n));
+    assert!(checkpoint.iteration > 4 * PROGRESS_INTERVAL);
+    assert!(checkpoint.iteration <= 5 * PROGRESS_INTERVAL);
+    assert_eq!(
+        checkpoint.a,
+        sqrt_ceil(&n) + &checkpoint.iteration - Integer::from(1)
+    );
+
+    assert!(checkpoint.is_consistent(&Integer::from(1)));
+    assert!(!checkpoint.is_consistent(&Integer::from(60)));
+    let mut edited = checkpoint.clone();
+    edited.a += Integer::from(1000);
+    assert!(!edited.is_consistent(&Integer::from(1)));
+    let mut edited = checkpoint.clone();
+    edited.first = &checkpoint.iteration + Integer::from(1);
+    assert!(!edited.is_consistent(&Integer::from(1)));
+
+    let mut search = FermatSearch::from_state(checkpoint.state());
+    assert_eq!(search.state(), checkpoint.state());
+    assert_eq!(
+        search.step(u64::MAX),
+        FermatOutcome::Found(Integer::from(SEMIPRIME_P), Integer::from(SEMIPRIME_Q))
+    );
+    assert_eq!(*search.iteration(), SEMIPRIME_ITERATION);
+    std::fs::remove_file(&path).unwrap();
+}
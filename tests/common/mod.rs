@@ -0,0 +1,19 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use malachite::Integer;
+
+/// The Mersenne prime `2^89 − 1`.
+pub fn m89() -> Integer {
+    (Integer::from(1) << 89u32) - Integer::from(1)
+}
+
+/// `1326970319 · 1926970321`, with factors 600000002 apart, so plain Fermat takes
+/// [`SEMIPRIME_ITERATION`] iterations to split it.
+pub const SEMIPRIME: u64 = 2557032421560902399;
+/// The smaller factor of [`SEMIPRIME`].
+pub const SEMIPRIME_P: u64 = 1326970319;
+/// The larger factor of [`SEMIPRIME`].
+pub const SEMIPRIME_Q: u64 = 1926970321;
+/// The Fermat iteration that splits [`SEMIPRIME`].
+pub const SEMIPRIME_ITERATION: u64 = 27897958;
@@ -0,0 +1,62 @@
+mod common;
+
+use common::m89;
+use diffsquare::cancel::CancelToken;
+use diffsquare::ecm::{ecm, Curve, Setup};
+use malachite::{Integer, Natural};
+
+#[test]
+fn test_montgomery_ladder() {
+    let n = Natural::from(1_000_000_007u64) * Natural::from(998_244_353u64);
+    let Setup::Curve(curve, p) = Curve::suyama(11, &n) else {
+        panic!("Expected a curve for σ = 11");
+    };
+
+    // [6]([7]P) = [42]P = [2]([21]P), compared projectively
+    let a = curve.multiply(
+        &Natural::from(6u32),
+        &curve.multiply(&Natural::from(7u32), &p),
+    );
+    let b = curve.double(&curve.multiply(&Natural::from(21u32), &p));
+    assert_eq!((&a.x * &b.z) % &n, (&b.x * &a.z) % &n);
+
+    // [5]P = [3]P + [2]P with difference [1]P
+    let p2 = curve.double(&p);
+    let p3 = curve.add(&p2, &p, &p);
+    let a = curve.add(&p3, &p2, &p);
+    let b = curve.multiply(&Natural::from(5u32), &p);
+    assert_eq!((&a.x * &b.z) % &n, (&b.x * &a.z) % &n);
+}
+
+#[test]
+fn test_ecm() {
+    let p = Integer::from(1_000_000_007u64);
+    let n = &p * m89();
+    let mut iter = Integer::from(0);
+
+    let result = ecm(&n, 2000, 100_000, 64, 0, &mut iter, &CancelToken::new());
+
+    assert_eq!(result, Some((p, m89())));
+    assert!(iter > 0);
+}
+
+#[test]
+fn test_ecm_trivial() {
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        ecm(
+            &Integer::from(1),
+            2000,
+            0,
+            4,
+            0,
+            &mut iter,
+            &CancelToken::new()
+        ),
+        None
+    );
+    assert_eq!(
+        ecm(&m89(), 2000, 0, 4, 0, &mut iter, &CancelToken::new()),
+        None
+    );
+}
@@ -1,4 +1,14 @@
-use diffsquare::factor::difference_of_squares;
+mod common;
+
+use common::{SEMIPRIME, SEMIPRIME_ITERATION, SEMIPRIME_P, SEMIPRIME_Q};
+use diffsquare::cancel::CancelToken;
+use diffsquare::factor::{
+    bounded_difference_of_squares, bounded_parallel_difference_of_squares, difference_of_squares,
+    factorize, factorize_with, parallel_difference_of_squares, FermatOutcome, SearchLimit,
+    Searched,
+};
+use diffsquare::progress::Silent;
+use diffsquare::sqrt::sqrt_ceil;
 use malachite::Integer;
 
 #[test]
@@ -83,3 +93,264 @@ fn big_numbers() {
         );
     }
 }
+
+#[test]
+fn test_difference_of_squares_large_prime() {
+    // 2^127 - 1 would otherwise keep the Fermat loop running practically forever
+    let n = (Integer::from(1) << 127u32) - Integer::from(1);
+    let mut iter = Integer::from(1);
+
+    let result = difference_of_squares(&n, &mut iter, 6, true);
+
+    assert!(
+        result.is_none(),
+        "Expected no factorization for prime {}",
+        n
+    );
+}
+
+#[test]
+fn test_factorize() {
+    // 2^3 · 3^2 · 101 · 103 · 1009^2 · 65537
+    let n = Integer::from(8u64 * 9 * 101 * 103 * 1009 * 1009 * 65537);
+    let mut iterations = Integer::from(0);
+
+    let factors = factorize(&n, &mut iterations, 6, true);
+
+    let expected: Vec<(Integer, u64)> = [
+        (2u64, 3u64),
+        (3, 2),
+        (101, 1),
+        (103, 1),
+        (1009, 2),
+        (65537, 1),
+    ]
+    .iter()
+    .map(|&(p, e)| (Integer::from(p), e))
+    .collect();
+    assert_eq!(factors, expected);
+}
+
+#[test]
+fn test_factorize_three_primes() {
+    // 104729 · 104743 is close to the third prime, so every split is a Fermat-friendly one
+    let primes = [104729u64, 104743, 10969629667];
+    let n = primes
+        .iter()
+        .fold(Integer::from(1), |acc, &p| acc * Integer::from(p));
+    let mut iterations = Integer::from(0);
+
+    let factors = factorize(&n, &mut iterations, 6, true);
+
+    let expected: Vec<(Integer, u64)> = primes.iter().map(|&p| (Integer::from(p), 1)).collect();
+    assert_eq!(factors, expected);
+    assert!(iterations > 0);
+}
+
+#[test]
+fn test_factorize_trivial() {
+    let mut iterations = Integer::from(0);
+    assert!(factorize(&Integer::from(1), &mut iterations, 6, true).is_empty());
+    assert_eq!(
+        factorize(&Integer::from(65537), &mut iterations, 6, true),
+        vec![(Integer::from(65537), 1)]
+    );
+}
+
+#[test]
+fn test_factorize_with_unsplit_cofactor() {
+    // The 90-bit cofactor is too wide for SQUFOF and the splitter never succeeds
+    let cofactor = Integer::from(1_000_000_007u64)
+        * Integer::from(1_000_000_009u64)
+        * Integer::from(1_000_000_021u64);
+    let n = Integer::from(12) * &cofactor;
+    let mut iterations = Integer::from(0);
+    let mut unfactored = Vec::new();
+
+    let factors = factorize_with(&n, &mut iterations, &mut unfactored, |_, _| None);
+
+    assert_eq!(factors, vec![(Integer::from(2), 2), (Integer::from(3), 1)]);
+    assert_eq!(unfactored, vec![cofactor]);
+}
+
+#[test]
+fn test_parallel_difference_of_squares() {
+    // 1000000007 · 1050000011 needs 304924 iterations, several rounds of chunks
+    let n = Integer::from(1050000018350000077u64);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+
+    for start in [1u64, 200000, 304924] {
+        let mut serial_iter = Integer::from(start);
+        let serial = difference_of_squares(&n, &mut serial_iter, 6, true);
+
+        let mut parallel_iter = Integer::from(start);
+        let parallel =
+            pool.install(|| parallel_difference_of_squares(&n, &mut parallel_iter, 6, true));
+
+        assert_eq!(
+            parallel,
+            Some((Integer::from(1000000007), Integer::from(1050000011)))
+        );
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel_iter, serial_iter);
+        assert_eq!(parallel_iter, 304924);
+    }
+}
+
+#[test]
+fn test_parallel_difference_of_squares_prime() {
+    let mut iter = Integer::from(1);
+    let n = Integer::from(1000000007);
+    assert!(parallel_difference_of_squares(&n, &mut iter, 6, true).is_none());
+}
+
+#[test]
+fn test_search_limit_exhausted() {
+    let n = Integer::from(SEMIPRIME);
+    let gap = Integer::from(SEMIPRIME_Q - SEMIPRIME_P);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+
+    for (max_iterations, max_gap_bits) in [
+        (Some(SEMIPRIME_ITERATION - 1), None),
+        (Some(1000), None),
+        (None, Some(29)),
+    ] {
+        let limit = SearchLimit {
+            max_iterations: max_iterations.map(Integer::from),
+            max_gap_bits,
+        };
+        let mut iter = Integer::from(1);
+        let outcome =
+            bounded_difference_of_squares(&n, &mut iter, &limit, &CancelToken::new(), &Silent);
+        let FermatOutcome::Exhausted { searched } = &outcome else {
+            panic!("expected exhaustion, got {outcome:?}");
+        };
+        let min_gap = searched.min_gap.as_ref().unwrap();
+        assert!(*min_gap <= gap);
+        if let Some(max) = max_iterations {
+            assert_eq!(iter, max + 1);
+        }
+        if max_gap_bits.is_some() {
+            assert!(*min_gap > 1 << 29);
+        }
+
+        let mut parallel_iter = Integer::from(1);
+        let parallel = pool.install(|| {
+            bounded_parallel_difference_of_squares(
+                &n,
+                &mut parallel_iter,
+                &limit,
+                &CancelToken::new(),
+                &Silent,
+            )
+        });
+        assert_eq!(parallel, outcome);
+        assert_eq!(parallel_iter, iter);
+    }
+
+    // The bound is tight just before the hit, and one more iteration finds it.
+    let limit = |max: u64| SearchLimit {
+        max_iterations: Some(Integer::from(max)),
+        max_gap_bits: None,
+    };
+    let mut iter = Integer::from(1);
+    assert_eq!(
+        bounded_difference_of_squares(
+            &n,
+            &mut iter,
+            &limit(SEMIPRIME_ITERATION - 1),
+            &CancelToken::new(),
+            &Silent
+        ),
+        FermatOutcome::Exhausted {
+            searched: Searched {
+                start: sqrt_ceil(&n),
+                end: sqrt_ceil(&n) + Integer::from(SEMIPRIME_ITERATION - 1),
+                min_gap: Some(gap)
+            }
+        }
+    );
+    let mut iter = Integer::from(1);
+    assert_eq!(
+        bounded_difference_of_squares(
+            &n,
+            &mut iter,
+            &limit(SEMIPRIME_ITERATION),
+            &CancelToken::new(),
+            &Silent
+        ),
+        FermatOutcome::Found(Integer::from(SEMIPRIME_P), Integer::from(SEMIPRIME_Q))
+    );
+}
+
+#[test]
+fn test_search_limit_started_past_the_hit() {
+    // Starting one iteration after the hit skips it, so no bound on q − p can be claimed,
+    // only the range of a that was searched.
+    let n = Integer::from(SEMIPRIME);
+    let start = sqrt_ceil(&n) + Integer::from(SEMIPRIME_ITERATION);
+    let limit = SearchLimit {
+        max_iterations: Some(Integer::from(SEMIPRIME_ITERATION + 1000)),
+        max_gap_bits: None,
+    };
+    let expected = FermatOutcome::Exhausted {
+        searched: Searched {
+            end: &start + Integer::from(1000),
+            start,
+            min_gap: None,
+        },
+    };
+
+    let mut iter = Integer::from(SEMIPRIME_ITERATION + 1);
+    let outcome =
+        bounded_difference_of_squares(&n, &mut iter, &limit, &CancelToken::new(), &Silent);
+    assert_eq!(outcome, expected);
+    assert_eq!(iter, SEMIPRIME_ITERATION + 1001);
+
+    let mut iter = Integer::from(SEMIPRIME_ITERATION + 1);
+    let outcome =
+        bounded_parallel_difference_of_squares(&n, &mut iter, &limit, &CancelToken::new(), &Silent);
+    assert_eq!(outcome, expected);
+
+    let cancelled = CancelToken::new();
+    cancelled.cancel();
+    let mut iter = Integer::from(SEMIPRIME_ITERATION + 1);
+    let FermatOutcome::Cancelled { searched } =
+        bounded_difference_of_squares(&n, &mut iter, &limit, &cancelled, &Silent)
+    else {
+        panic!("expected cancellation");
+    };
+    assert_eq!(searched.min_gap, None);
+}
+
+#[test]
+fn test_search_limit_large_input() {
+    // Beyond 64 bits the search runs on fixed-width limbs.
+    let n = Integer::from(4294967311u128 * 4294967357 * 4294967371);
+    let limit = SearchLimit {
+        max_iterations: Some(Integer::from(50_000)),
+        max_gap_bits: None,
+    };
+    let mut iter = Integer::from(1);
+    match bounded_difference_of_squares(&n, &mut iter, &limit, &CancelToken::new(), &Silent) {
+        FermatOutcome::Exhausted { searched } => assert!(searched.min_gap.unwrap() > 0),
+        outcome => panic!("expected exhaustion, got {outcome:?}"),
+    }
+    assert_eq!(iter, 50_001);
+    assert_eq!(
+        bounded_difference_of_squares(
+            &Integer::from(101),
+            &mut iter,
+            &limit,
+            &CancelToken::new(),
+            &Silent
+        ),
+        FermatOutcome::NoFactor
+    );
+}
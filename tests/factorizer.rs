@@ -0,0 +1,177 @@
+mod common;
+
+use common::{SEMIPRIME, SEMIPRIME_ITERATION, SEMIPRIME_P, SEMIPRIME_Q};
+use diffsquare::cancel::CancelToken;
+use diffsquare::factor::SearchLimit;
+use diffsquare::factorizer::Factorizer;
+use diffsquare::progress::{Progress, ProgressSink};
+use diffsquare::split::{Failure, Method};
+use malachite::Integer;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread::ThreadId;
+use std::time::Duration;
+
+/// Keeps the iteration of every found pair.
+#[derive(Default)]
+struct Found(Mutex<Vec<Integer>>);
+
+impl ProgressSink for Found {
+    fn update(&self, _progress: &Progress) {}
+
+    fn found(&self, progress: &Progress) {
+        self.0.lock().unwrap().push(progress.iteration.clone());
+    }
+}
+
+#[test]
+fn test_factorizer_split() {
+    let outcome = Factorizer::new().split(&Integer::from(5959));
+    assert_eq!(outcome.result, Ok((Integer::from(59), Integer::from(101))));
+    assert_eq!(outcome.method, Method::Fermat);
+    assert_eq!(outcome.iterations, 3);
+
+    let n = Integer::from(SEMIPRIME);
+    let found = Found::default();
+    let outcome = Factorizer::new()
+        .start(Integer::from(27_000_000))
+        .threads(2)
+        .unwrap()
+        .progress(&found)
+        .split(&n);
+    assert_eq!(
+        outcome.result,
+        Ok((Integer::from(SEMIPRIME_P), Integer::from(SEMIPRIME_Q)))
+    );
+    assert_eq!(outcome.iterations, SEMIPRIME_ITERATION);
+    assert_eq!(
+        *found.0.lock().unwrap(),
+        [Integer::from(SEMIPRIME_ITERATION)]
+    );
+
+    let outcome = Factorizer::new()
+        .limit(SearchLimit {
+            max_iterations: Some(Integer::from(100)),
+            max_gap_bits: None,
+        })
+        .split(&n);
+    let failure = outcome.result.unwrap_err();
+    assert!(matches!(failure, Failure::Exhausted { .. }));
+    assert_eq!(failure.min_gap(), Some(&Integer::from(1132016)));
+    assert_eq!(outcome.iterations, 101);
+
+    // Started past the hit, the search misses it and makes no claim about q − p
+    let outcome = Factorizer::new()
+        .start(Integer::from(SEMIPRIME_ITERATION + 1))
+        .limit(SearchLimit {
+            max_iterations: Some(Integer::from(SEMIPRIME_ITERATION + 100)),
+            max_gap_bits: None,
+        })
+        .split(&n);
+    let failure = outcome.result.unwrap_err();
+    assert!(matches!(failure, Failure::Exhausted { .. }));
+    assert_eq!(failure.min_gap(), None);
+    assert_eq!(outcome.iterations, SEMIPRIME_ITERATION + 101);
+}
+
+/// Keeps the thread each found pair was reported on.
+#[derive(Default)]
+struct FoundOn(Mutex<Vec<ThreadId>>);
+
+impl ProgressSink for FoundOn {
+    fn update(&self, _progress: &Progress) {}
+
+    fn found(&self, _progress: &Progress) {
+        self.0.lock().unwrap().push(std::thread::current().id());
+    }
+}
+
+#[test]
+fn test_factorizer_clones_share_the_pool() {
+    // A one-thread pool shared by both clones runs both splits on the same worker
+    let found = FoundOn::default();
+    let factorizer = Factorizer::new().threads(1).unwrap().progress(&found);
+    let clone = factorizer.clone();
+    assert!(factorizer.split(&Integer::from(5959)).found().is_some());
+    assert!(clone.split(&Integer::from(5959)).found().is_some());
+
+    let threads = found.0.lock().unwrap();
+    assert_eq!(threads.len(), 2);
+    assert_eq!(threads[0], threads[1]);
+    assert_ne!(threads[0], std::thread::current().id());
+}
+
+#[test]
+fn test_factorizer_cancel_and_timeout() {
+    let n = Integer::from(SEMIPRIME);
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    let outcome = Factorizer::new().cancel(cancel).split(&n);
+    assert!(matches!(outcome.result, Err(Failure::Cancelled { .. })));
+
+    let factorizer = Factorizer::new().timeout(Duration::ZERO);
+    let outcome = factorizer.split(&n);
+    assert!(matches!(outcome.result, Err(Failure::TimedOut { .. })));
+    // (2^40 + 15)(2^80 + 13) is too wide for SQUFOF, so the timed-out split is what stops it
+    let far = Integer::from_str("1329227995803049760198040791552098499").unwrap();
+    let factorization = factorizer.factorize(&far);
+    assert!(factorization.cancelled);
+    assert_eq!(factorization.unfactored, [far]);
+
+    // A run that finished every split is complete, even if the token is cancelled by then
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    let factorization = Factorizer::new()
+        .cancel(cancel)
+        .factorize(&Integer::from(24 * 5959));
+    assert!(!factorization.cancelled);
+    assert!(factorization.unfactored.is_empty());
+    assert_eq!(factorization.factors.len(), 4);
+
+    // The timeout counts from each run, and does not cancel the caller's token.
+    let cancel = CancelToken::new();
+    let factorizer = Factorizer::new()
+        .cancel(cancel.clone())
+        .timeout(Duration::from_secs(3600));
+    assert!(factorizer.split(&Integer::from(5959)).found().is_some());
+    assert!(!cancel.is_cancelled());
+}
+
+#[test]
+fn test_factorizer_factorize() {
+    // 2^3 · 3 · 59 · 101 · 1000003 · 1000033
+    let n = Integer::from(24u64 * 5959) * Integer::from(1000003u64 * 1000033);
+    for method in [Method::Fermat, Method::Rho { seed: 2 }, Method::Qs] {
+        let factorization = Factorizer::new().method(method).factorize(&n);
+        assert!(!factorization.cancelled);
+        let primes: Vec<(Integer, u64)> = [
+            (2, 3),
+            (3, 1),
+            (59, 1),
+            (101, 1),
+            (1000003, 1),
+            (1000033, 1),
+        ]
+        .into_iter()
+        .map(|(p, e)| (Integer::from(p), e))
+        .collect();
+        assert_eq!(factorization.factors, primes);
+    }
+}
+
+#[test]
+fn test_factorizer_unfactored() {
+    // Stage bounds this small never split the 90-bit cofactor
+    let cofactor = Integer::from(1_000_000_007u64)
+        * Integer::from(1_000_000_009u64)
+        * Integer::from(1_000_000_021u64);
+    let factorization = Factorizer::new()
+        .method(Method::Pm1 { b1: 3, b2: 4 })
+        .factorize(&(Integer::from(12) * &cofactor));
+    assert_eq!(
+        factorization.factors,
+        [(Integer::from(2), 2), (Integer::from(3), 1)]
+    );
+    assert_eq!(factorization.unfactored, [cofactor]);
+    assert!(!factorization.cancelled);
+}
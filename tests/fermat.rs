@@ -0,0 +1,119 @@
+mod common;
+
+use common::{SEMIPRIME, SEMIPRIME_ITERATION, SEMIPRIME_P, SEMIPRIME_Q};
+use diffsquare::factor::{FermatOutcome, Searched};
+use diffsquare::fermat::{FermatSearch, FermatState};
+use malachite::Integer;
+
+#[test]
+fn test_fermat_search_steps() {
+    let mut search = FermatSearch::new(&Integer::from(5959));
+    assert_eq!(*search.a(), 78);
+    assert_eq!(search.residue(), 125);
+    // 2·⌈√(79² − 5959)⌉ = 34 once a = 78 is ruled out
+    assert_eq!(
+        search.step(1),
+        FermatOutcome::Exhausted {
+            searched: Searched {
+                start: Integer::from(78),
+                end: Integer::from(79),
+                min_gap: Some(Integer::from(34))
+            }
+        }
+    );
+    assert_eq!(*search.iteration(), 2);
+    assert_eq!(*search.a(), 79);
+    assert_eq!(
+        search.step(10),
+        FermatOutcome::Found(Integer::from(59), Integer::from(101))
+    );
+    // Stopped at the hit, 80² − 5959 = 21²
+    assert_eq!(*search.iteration(), 3);
+    assert_eq!(search.residue(), 441);
+
+    // Same bound and iteration as a search limited to 100 iterations
+    let mut search = FermatSearch::new(&Integer::from(SEMIPRIME));
+    let start = search.a().clone();
+    assert_eq!(
+        search.step(100),
+        FermatOutcome::Exhausted {
+            searched: Searched {
+                end: &start + Integer::from(100),
+                start,
+                min_gap: Some(Integer::from(1132016))
+            }
+        }
+    );
+    assert_eq!(*search.iteration(), 101);
+
+    assert_eq!(
+        FermatSearch::new(&Integer::from(65537)).step(1000),
+        FermatOutcome::NoFactor
+    );
+}
+
+#[test]
+fn test_fermat_search_interleaved_and_resumed() {
+    let slow = Integer::from(SEMIPRIME);
+    let fast = Integer::from(1000003u64 * 1000033);
+    let mut searches = [FermatSearch::new(&slow), FermatSearch::new(&fast)];
+    let mut found = Vec::new();
+    while found.len() < 2 {
+        for search in searches.iter_mut() {
+            if found.iter().any(|(n, _, _)| n == search.n()) {
+                continue;
+            }
+            // Pause and resume between every step
+            let state = search.state();
+            *search = FermatSearch::from_state(state.clone());
+            assert_eq!(search.state(), state);
+            if let FermatOutcome::Found(p, q) = search.step(1 << 20) {
+                found.push((search.n().clone(), p, q));
+            }
+        }
+    }
+    assert_eq!(
+        found,
+        [
+            (fast, Integer::from(1000003), Integer::from(1000033)),
+            (
+                slow.clone(),
+                Integer::from(SEMIPRIME_P),
+                Integer::from(SEMIPRIME_Q)
+            ),
+        ]
+    );
+    assert_eq!(*searches[0].iteration(), SEMIPRIME_ITERATION);
+
+    // A state below ⌈√n⌉ starts at ⌈√n⌉ with the iteration moved along
+    let search = FermatSearch::from_state(FermatState {
+        n: Integer::from(5959),
+        a: Integer::from(70),
+        iteration: Integer::from(1),
+        start: Integer::from(70),
+    });
+    assert_eq!(*search.a(), 78);
+    assert_eq!(*search.iteration(), 9);
+    assert_eq!(search.state().start, 78);
+}
+
+#[test]
+fn test_fermat_search_started_past_the_hit() {
+    // 80 splits 5959, so a search from 81 finds nothing and proves no bound
+    let mut search = FermatSearch::from_state(FermatState {
+        n: Integer::from(5959),
+        a: Integer::from(81),
+        iteration: Integer::from(4),
+        start: Integer::from(81),
+    });
+    assert_eq!(
+        search.step(10),
+        FermatOutcome::Exhausted {
+            searched: Searched {
+                start: Integer::from(81),
+                end: Integer::from(91),
+                min_gap: None
+            }
+        }
+    );
+}
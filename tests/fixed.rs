@@ -0,0 +1,111 @@
+use diffsquare::factor::{difference_of_squares, scan};
+use diffsquare::fixed::{FermatInt, Fixed};
+use diffsquare::sqrt::{is_probably_square, sqrt_ceil};
+use diffsquare::wheel::{Wheel, WHEEL_PRIMES};
+use malachite::{Integer, Natural};
+use std::str::FromStr;
+
+fn big(s: &str) -> Integer {
+    Integer::from_str(s).unwrap()
+}
+
+#[test]
+fn test_fixed_arithmetic() {
+    let max_128 = Natural::from(u128::MAX);
+    let mut f = Fixed::<3>::from_natural(&max_128).unwrap();
+    assert_eq!(f.to_natural(), max_128);
+    assert!(Fixed::<1>::from_natural(&max_128).is_none());
+
+    // Carries run through every limb
+    assert!(!f.add_small(1));
+    assert_eq!(f.limbs, [0, 0, 1]);
+    let mut top = Fixed::<2> {
+        limbs: [u64::MAX, u64::MAX],
+    };
+    assert!(top.add_small(1));
+    assert_eq!(top.limbs, [0, 0]);
+
+    let x = Fixed::<3> {
+        limbs: [u64::MAX, 1, 0],
+    };
+    assert!(!f.add_mul_small(&x, 3));
+    assert_eq!(
+        f.to_natural(),
+        (max_128 + Natural::from(1u32)) + x.to_natural() * Natural::from(3u32)
+    );
+
+    for m in [3u64, 64, 45045, 4294967291] {
+        assert_eq!(
+            Natural::from(f.rem_small(m)),
+            f.to_natural() % Natural::from(m)
+        );
+    }
+}
+
+#[test]
+fn test_fixed_matches_integer() {
+    let n = big("1185347238455264845036890973188333233016744230339266032293033");
+    let a0 = sqrt_ceil(&n);
+    let mut a = a0.clone();
+    let mut x2 = &a0 * &a0 - &n;
+    let mut fa = Fixed::<4>::from_integer(&a).unwrap();
+    let mut fx2 = Fixed::<4>::from_integer(&x2).unwrap();
+
+    for t in [0u64, 1, 2, 15015, u64::MAX >> 1, 12345] {
+        Integer::shift(&mut a, &mut x2, t);
+        Fixed::shift(&mut fa, &mut fx2, t);
+        assert_eq!(fa.to_integer(), a);
+        assert_eq!(fx2.to_integer(), x2);
+        assert_eq!(fx2.is_probably_square(), is_probably_square(&x2));
+    }
+
+    let square = Fixed::<8>::from_integer(&(&n * &n)).unwrap();
+    assert_eq!(square.sqrt_exact().map(|r| r.to_integer()), Some(n.clone()));
+    let mut next = square;
+    next.add_small(1);
+    assert_eq!(next.sqrt_exact(), None);
+    assert!(Fixed::<4>::from_integer(&Integer::from(-1)).is_none());
+}
+
+#[test]
+fn test_scan_widths_agree() {
+    // (n, p, q, offset of (p + q) / 2 from ⌈√n⌉) for 200- and 600-bit n
+    let cases = [
+        (
+            "1185347238455264845036890973188333233016744230339266032293033",
+            "1088736533075791039887641574503",
+            "1088736533077051039887641574511",
+            182275u64,
+        ),
+        (
+            "2930262679675403382944218793759621182760238105449300794419060518183109957872097026079877238579175108971700125847501501179966764616772299866002868518314491627211838431762910694209281",
+            "1711801004695172908795931930834357685734356768807450055454925513912119453466390624891687189",
+            "1711801004695172908795931930834357685734358568807450055454925513912119453466390624891687229",
+            236592,
+        ),
+    ];
+
+    for (n, p, q, offset) in cases {
+        let (n, p, q) = (big(n), big(p), big(q));
+        let a0 = sqrt_ceil(&n);
+        let wheel = Wheel::new(&n, WHEEL_PRIMES);
+        let expected = Some((offset, (&p + &q) >> 1u32, (&q - &p) >> 1u32));
+
+        let len = 1 << 20;
+        assert_eq!(
+            scan::<Integer, _>(&n, &wheel, a0.clone(), len, |_| true),
+            expected
+        );
+        assert_eq!(
+            scan::<Fixed<18>, _>(&n, &wheel, a0.clone(), len, |_| true),
+            expected
+        );
+
+        let mut iteration = Integer::from(0);
+        assert_eq!(
+            difference_of_squares(&n, &mut iteration, 4, true),
+            Some((p, q))
+        );
+        assert_eq!(iteration, offset + 1);
+    }
+}
@@ -0,0 +1,55 @@
+use diffsquare::cancel::CancelToken;
+use diffsquare::hart::{hart, one_line_step};
+use malachite::{Integer, Natural};
+
+#[test]
+fn test_hart_ratio() {
+    // q ≈ 3p: Fermat needs about 10^11 iterations, Hart's i = 3 lands on the split
+    let p = Integer::from(1000000000039u64);
+    let q = Integer::from(3000000000121u64);
+    let mut iter = Integer::from(0);
+
+    let result = hart(&(&p * &q), &mut iter, &CancelToken::new());
+
+    assert_eq!(result, Some((p, q)));
+    assert!(iter > 0 && iter < 10);
+}
+
+#[test]
+fn test_hart_close_factors() {
+    // The Fermat half of the race catches neighbouring primes right away
+    let p = Integer::from(1000000007);
+    let q = Integer::from(1000000009);
+    let mut iter = Integer::from(0);
+
+    assert_eq!(
+        hart(&(&p * &q), &mut iter, &CancelToken::new()),
+        Some((p, q))
+    );
+    assert_eq!(iter, 1);
+}
+
+#[test]
+fn test_one_line_step() {
+    // 5959 · 8 = 47672, ⌈√47672⌉ = 219, 219² mod 5959 = 289 = 17², gcd(202, 5959) = 101
+    let n = Integer::from(5959);
+    assert_eq!(
+        one_line_step(&n, &Integer::from(5959 * 8)),
+        Some(Natural::from(101u32))
+    );
+    // 5959 · 3 = 17877, ⌈√17877⌉ = 134, 134² mod 5959 = 79 is not a square
+    assert_eq!(one_line_step(&n, &Integer::from(5959 * 3)), None);
+}
+
+#[test]
+fn test_hart_trivial() {
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        hart(&Integer::from(65537), &mut iter, &CancelToken::new()),
+        None
+    );
+    assert_eq!(
+        hart(&Integer::from(2 * 65537), &mut iter, &CancelToken::new()),
+        Some((Integer::from(2), Integer::from(65537)))
+    );
+}
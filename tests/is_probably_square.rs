@@ -1,4 +1,4 @@
-use diffsquare::sqrt::is_probably_square;
+use diffsquare::sqrt::{is_probably_square, legendre_probably_square, SquareFilter};
 use malachite::Integer;
 
 #[test]
@@ -24,3 +24,46 @@ fn test_non_perfect_squares() {
         );
     }
 }
+
+#[test]
+fn test_square_filter_matches_legendre_check() {
+    let n = Integer::from(999962000357u64);
+    let a0 = Integer::from(999981u64);
+    let mut filter = SquareFilter::new(&n, &a0, 0);
+
+    for offset in 0..20_000u64 {
+        let a = &a0 + Integer::from(offset);
+        let x2 = &a * &a - &n;
+        assert_eq!(filter.passes(), is_probably_square(&x2), "offset {offset}");
+        filter.advance(1);
+    }
+
+    // Steps longer than some moduli, as after a wheel start
+    let mut filter = SquareFilter::new(&n, &a0, 0);
+    let mut offset = 0u64;
+    for step in (1..200u64).cycle().take(2_000) {
+        filter.advance(step);
+        offset += step;
+        let a = &a0 + Integer::from(offset);
+        let x2 = &a * &a - &n;
+        assert_eq!(filter.passes(), is_probably_square(&x2), "offset {offset}");
+    }
+}
+
+#[test]
+fn test_bitset_check_agrees_with_legendre_check() {
+    let mut x = Integer::from(123456789012345u64);
+    for i in 0..5_000u64 {
+        let square = &x * &x;
+        assert!(is_probably_square(&square), "{square} is a square");
+        assert!(legendre_probably_square(&square));
+
+        // Every candidate passing the bitsets must also pass the Legendre tables.
+        let candidate = &square + Integer::from(i + 1);
+        if is_probably_square(&candidate) {
+            assert!(legendre_probably_square(&candidate), "{candidate}");
+        }
+        x += Integer::from(7919u64);
+    }
+    assert!(!is_probably_square(&Integer::from(-4)));
+}
@@ -1,8 +1,8 @@
-use diffsquare::legendre::LEGENDRE_TABLES;
+use diffsquare::legendre::{QrTable, LEGENDRE_TABLES, QR_11, QR_63, QR_64, QR_65, QR_ODD_MODULUS};
 
 #[test]
 fn test_legendre_table_lengths() {
-    for &(p, ref table) in LEGENDRE_TABLES.iter() {
+    for &(p, table) in LEGENDRE_TABLES.iter() {
         assert_eq!(
             table.len(),
             p as usize,
@@ -14,7 +14,7 @@ fn test_legendre_table_lengths() {
 
 #[test]
 fn test_legendre_quadratic_residue() {
-    for &(p, ref table) in LEGENDRE_TABLES.iter() {
+    for &(p, table) in LEGENDRE_TABLES.iter() {
         for a in 1..p {
             let residue = (a * a) % p;
             let chi = table[residue as usize];
@@ -26,3 +26,29 @@ fn test_legendre_quadratic_residue() {
         }
     }
 }
+
+#[test]
+fn test_qr_table_counts() {
+    assert_eq!(QR_64.count(), 12);
+    assert_eq!(QR_63.count(), 16);
+    assert_eq!(QR_65.count(), 21);
+    assert_eq!(QR_11.count(), 6);
+    assert_eq!(QR_ODD_MODULUS, 45045);
+}
+
+#[test]
+fn test_qr_table_matches_brute_force() {
+    const QR_100: QrTable<2> = QrTable::new(100);
+    let check = |modulus: u64, contains: &dyn Fn(u64) -> bool| {
+        for r in 0..modulus {
+            let expected = (0..modulus).any(|x| x * x % modulus == r);
+            assert_eq!(contains(r), expected, "r = {} mod {}", r, modulus);
+            assert_eq!(contains(r + modulus), expected);
+        }
+    };
+    check(64, &|r| QR_64.contains(r));
+    check(63, &|r| QR_63.contains(r));
+    check(65, &|r| QR_65.contains(r));
+    check(11, &|r| QR_11.contains(r));
+    check(100, &|r| QR_100.contains(r));
+}
@@ -0,0 +1,53 @@
+use diffsquare::cancel::CancelToken;
+use diffsquare::lehman::lehman;
+use malachite::Integer;
+
+#[test]
+fn test_lehman_unbalanced() {
+    // p/q ≈ 1/1000: Fermat would need about 10^13 iterations
+    let p = Integer::from(1000003);
+    let q = Integer::from(1000000007);
+    let mut iter = Integer::from(0);
+
+    let result = lehman(&(&p * &q), &mut iter, &CancelToken::new());
+
+    assert_eq!(result, Some((p, q)));
+    assert!(iter > 0);
+}
+
+#[test]
+fn test_lehman_small_factor() {
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        lehman(
+            &Integer::from(101u64 * 1000000007),
+            &mut iter,
+            &CancelToken::new()
+        ),
+        Some((Integer::from(101), Integer::from(1000000007)))
+    );
+    assert_eq!(
+        lehman(&Integer::from(6), &mut iter, &CancelToken::new()),
+        Some((Integer::from(2), Integer::from(3)))
+    );
+}
+
+#[test]
+fn test_lehman_proves_primality() {
+    // The search is exhaustive, so None means prime.
+    for n in [2u64, 3, 5, 97, 65537, 1000000007, 999999999989] {
+        let mut iter = Integer::from(0);
+        assert_eq!(
+            lehman(&Integer::from(n), &mut iter, &CancelToken::new()),
+            None,
+            "{n}"
+        );
+    }
+    for n in [4u64, 25, 1000003u64 * 1000003, 999999999989 * 3] {
+        let mut iter = Integer::from(0);
+        assert!(
+            lehman(&Integer::from(n), &mut iter, &CancelToken::new()).is_some(),
+            "{n}"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{SEMIPRIME, SEMIPRIME_ITERATION, SEMIPRIME_P, SEMIPRIME_Q};
+use diffsquare::native::{scan, sqrt_exact_u128, PLAIN_SPAN};
+use diffsquare::wheel::{Wheel, WHEEL_PRIMES};
+use malachite::Integer;
+
+fn isqrt_ceil(n: u64) -> u64 {
+    let r = (n as f64).sqrt() as u64;
+    (r.saturating_sub(2)..)
+        .find(|&a| a as u128 * a as u128 >= n as u128)
+        .unwrap()
+}
+
+#[test]
+fn test_sqrt_exact_u128() {
+    for root in [0u64, 1, 2, 12345, 4294967295, 4294967296, (1 << 63) - 1] {
+        let x = root as u128 * root as u128;
+        assert_eq!(sqrt_exact_u128(x), Some(root), "root {root}");
+        if root > 1 {
+            assert_eq!(sqrt_exact_u128(x - 1), None);
+        }
+        assert_eq!(
+            sqrt_exact_u128(x + 1),
+            if root == 0 { Some(1) } else { None }
+        );
+    }
+    for x in 0..10_000u128 {
+        let root = (x as f64).sqrt() as u128;
+        assert_eq!(sqrt_exact_u128(x).is_some(), root * root == x, "x = {x}");
+    }
+}
+
+#[test]
+fn test_scan_finds_first_hit() {
+    // (p, q, expected offset of (p + q) / 2 from ⌈√n⌉), inside and past the plain span
+    let cases: [(u64, u64, u64); 4] = [
+        (665179, 666187, 0),
+        (604631369, 604731371, 2),
+        (1621498427, 1721498453, 747999),
+        (SEMIPRIME_P, SEMIPRIME_Q, SEMIPRIME_ITERATION - 1),
+    ];
+
+    for (p, q, expected) in cases {
+        let n = p * q;
+        let a0 = isqrt_ceil(n);
+        let wheel = Wheel::new(&Integer::from(n), WHEEL_PRIMES);
+
+        for prebuilt in [None, Some(&wheel)] {
+            let hit = scan(n, prebuilt, a0, n - a0, |offset, a, x2| {
+                assert_eq!(a, a0 + offset);
+                assert_eq!(x2, a as u128 * a as u128 - n as u128);
+                true
+            });
+            assert_eq!(hit, Some((expected, (p + q) / 2, (q - p) / 2)), "n = {n}");
+        }
+    }
+    const { assert!(PLAIN_SPAN < 747999) };
+}
+
+#[test]
+fn test_scan_stops_and_handles_even_input() {
+    let n = SEMIPRIME;
+    let a0 = isqrt_ceil(n);
+    assert_eq!(scan(n, None, a0, n - a0, |offset, _, _| offset == 0), None);
+
+    // 4m = (m + 1)² − (m − 1)²; 2 mod 4 has no solution at all.
+    assert_eq!(scan(4 * 3, None, 4, 8, |_, _, _| true), Some((0, 4, 2)));
+    assert_eq!(
+        scan(4 * 1000003, None, 2001, 1 << 21, |_, _, _| true).map(|h| h.1),
+        Some(1000004)
+    );
+    assert_eq!(scan(2 * 1000003, None, 1415, 1 << 21, |_, _, _| true), None);
+}
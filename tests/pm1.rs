@@ -0,0 +1,114 @@
+mod common;
+
+use common::m89;
+use diffsquare::cancel::CancelToken;
+use diffsquare::pm1::pollard_pm1;
+use diffsquare::prime::{primes_up_to, PrimeRange, SEGMENT};
+use malachite::Integer;
+use std::time::Duration;
+
+#[test]
+fn test_primes_up_to() {
+    assert_eq!(primes_up_to(1), Vec::<u64>::new());
+    assert_eq!(primes_up_to(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+    assert_eq!(primes_up_to(1_000_000).len(), 78498);
+}
+
+#[test]
+fn test_prime_range() {
+    let primes = primes_up_to(1_000_000);
+    for (lo, hi) in [
+        (0, 1_000_000),
+        (1, 2),
+        (2, 3),
+        (7, 30),
+        (SEGMENT - 10, 3 * SEGMENT + 7),
+    ] {
+        let expected: Vec<u64> = primes
+            .iter()
+            .copied()
+            .filter(|&p| lo < p && p <= hi)
+            .collect();
+        assert_eq!(
+            PrimeRange::new(lo, hi).collect::<Vec<_>>(),
+            expected,
+            "({lo}, {hi}]"
+        );
+    }
+    assert_eq!(PrimeRange::new(30, 30).next(), None);
+    assert_eq!(
+        PrimeRange::new(1_000_000_000_000, 1_000_000_001_000)
+            .take(2)
+            .collect::<Vec<_>>(),
+        [1_000_000_000_039, 1_000_000_000_061]
+    );
+}
+
+#[test]
+fn test_pollard_pm1_stage1() {
+    // p − 1 = 2 · 3 · 23 · 349 · 547 · 613 · 641 · 701 · 919
+    let p = Integer::from(6668741344421744779u64);
+    let n = &p * m89();
+    let mut iter = Integer::from(0);
+
+    let result = pollard_pm1(&n, 1000, 0, &mut iter, &CancelToken::new());
+
+    assert_eq!(result, Some((p, m89())));
+    assert!(iter > 0);
+}
+
+#[test]
+fn test_pollard_pm1_stage2() {
+    // p − 1 = 2 · 5 · 13 · 97 · 349 · 353 · 541 · 659 · 40009
+    let p = Integer::from(22159277450834568071u128);
+    let n = &p * m89();
+
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        pollard_pm1(&n, 1000, 0, &mut iter, &CancelToken::new()),
+        None
+    );
+
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        pollard_pm1(&n, 1000, 50_000, &mut iter, &CancelToken::new()),
+        Some((p, m89()))
+    );
+}
+
+#[test]
+fn test_pollard_pm1_trivial() {
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        pollard_pm1(&Integer::from(1), 1000, 0, &mut iter, &CancelToken::new()),
+        None
+    );
+    assert_eq!(
+        pollard_pm1(&m89(), 1000, 0, &mut iter, &CancelToken::new()),
+        None
+    );
+}
+
+#[test]
+fn test_pollard_pm1_stage2_small_primes() {
+    // 43 − 1 = 2 · 3 · 7: 7 divides the giant-step width and has no baby step
+    let n = Integer::from(43) * m89();
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        pollard_pm1(&n, 3, 10, &mut iter, &CancelToken::new()),
+        Some((Integer::from(43), m89()))
+    );
+}
+
+#[test]
+fn test_pollard_pm1_large_b2() {
+    // Stage 2 sieves one segment at a time, so a huge bound only costs time
+    let n = m89() * ((Integer::from(1) << 107u32) - Integer::from(1));
+    let mut iter = Integer::from(0);
+    let cancel = CancelToken::with_timeout(Duration::from_millis(200));
+    assert_eq!(
+        pollard_pm1(&n, 1000, 100_000_000_000, &mut iter, &cancel),
+        None
+    );
+    assert!(iter > 168);
+}
@@ -0,0 +1,48 @@
+use diffsquare::cancel::CancelToken;
+use diffsquare::pp1::{lucas_v, williams_pp1};
+use malachite::{Integer, Natural};
+
+#[test]
+fn test_lucas_v() {
+    // V_k(3): 2, 3, 7, 18, 47, 123, 322, ...
+    let n = Natural::from(1_000_003u32);
+    let expected = [2u32, 3, 7, 18, 47, 123, 322];
+    for (k, &v) in expected.iter().enumerate() {
+        assert_eq!(
+            lucas_v(&Natural::from(3u32), &Natural::from(k as u64), &n),
+            Natural::from(v)
+        );
+    }
+}
+
+#[test]
+fn test_williams_pp1() {
+    // p + 1 = 2 · 3 · 5 · 71 · 139 · 149 · 157 · 181 · 257 · 631 · 941, while p − 1 is not smooth
+    let p = Integer::from(191298253644913014569u128);
+    let q = Integer::from(100000000000000000039u128);
+    let n = &p * &q;
+    let mut iter = Integer::from(0);
+
+    let result = williams_pp1(&n, 1000, &mut iter, &CancelToken::new());
+
+    assert_eq!(result, Some((q, p)));
+    assert!(iter > 0);
+}
+
+#[test]
+fn test_williams_pp1_trivial() {
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        williams_pp1(&Integer::from(1), 1000, &mut iter, &CancelToken::new()),
+        None
+    );
+    assert_eq!(
+        williams_pp1(
+            &Integer::from(100000000000000000039u128),
+            1000,
+            &mut iter,
+            &CancelToken::new()
+        ),
+        None
+    );
+}
@@ -0,0 +1,50 @@
+use diffsquare::prime::{is_probable_prime, miller_rabin, strong_lucas};
+use malachite::{Integer, Natural};
+
+#[test]
+fn test_small_values() {
+    let primes = [2u64, 3, 5, 7, 97, 101, 7919, 65537];
+    for &n in &primes {
+        assert!(is_probable_prime(&Integer::from(n)), "{} is prime", n);
+    }
+
+    let composites = [0u64, 1, 4, 9, 15, 91, 5959, 10403];
+    for &n in &composites {
+        assert!(!is_probable_prime(&Integer::from(n)), "{} is composite", n);
+    }
+    assert!(!is_probable_prime(&Integer::from(-7)));
+}
+
+#[test]
+fn test_pseudoprimes() {
+    // Carmichael numbers and strong pseudoprimes to base 2
+    let base_2 = [561u64, 1105, 1729, 2047, 3277, 4033, 4681, 3215031751];
+    for &n in &base_2 {
+        assert!(!is_probable_prime(&Integer::from(n)), "{} is composite", n);
+    }
+    assert!(miller_rabin(&Natural::from(2047u32), 2));
+
+    // Strong Lucas pseudoprimes
+    let lucas = [5459u64, 5777, 10877, 16109, 18971];
+    for &n in &lucas {
+        assert!(
+            strong_lucas(&Natural::from(n)),
+            "{} is a strong Lucas pseudoprime",
+            n
+        );
+        assert!(!is_probable_prime(&Integer::from(n)), "{} is composite", n);
+    }
+}
+
+#[test]
+fn test_large_values() {
+    let m127 = (Integer::from(1) << 127u32) - Integer::from(1);
+    let m521 = (Integer::from(1) << 521u32) - Integer::from(1);
+    assert!(is_probable_prime(&m127));
+    assert!(is_probable_prime(&m521));
+    assert!(strong_lucas(m521.unsigned_abs_ref()));
+    assert!(!is_probable_prime(&(&m127 * &m521)));
+
+    let m128 = (Integer::from(1) << 128u32) - Integer::from(1);
+    assert!(!is_probable_prime(&m128));
+}
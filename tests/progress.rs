@@ -0,0 +1,137 @@
+mod common;
+
+use common::{SEMIPRIME, SEMIPRIME_ITERATION, SEMIPRIME_P, SEMIPRIME_Q};
+use diffsquare::cancel::CancelToken;
+use diffsquare::factor::{
+    bounded_difference_of_squares, bounded_parallel_difference_of_squares, FermatOutcome,
+    SearchLimit, PROGRESS_INTERVAL,
+};
+use diffsquare::progress::{Progress, ProgressSink, Silent};
+use diffsquare::ratio::{ratio_fermat, Ratio};
+use diffsquare::sqrt::sqrt_ceil;
+use malachite::Integer;
+use std::cell::RefCell;
+use std::time::Duration;
+
+/// `(iteration, a, p, q, elapsed)` of one update.
+type Update = (Integer, Integer, Integer, Integer, Duration);
+
+/// Keeps every update, and the found pair separately.
+#[derive(Default)]
+struct Recorder {
+    updates: RefCell<Vec<Update>>,
+    found: RefCell<Vec<(Integer, Integer, Integer)>>,
+}
+
+impl ProgressSink for Recorder {
+    fn update(&self, progress: &Progress) {
+        self.updates.borrow_mut().push((
+            progress.iteration.clone(),
+            progress.a.clone(),
+            progress.p.clone(),
+            progress.q.clone(),
+            progress.elapsed,
+        ));
+    }
+
+    fn found(&self, progress: &Progress) {
+        self.found.borrow_mut().push((
+            progress.iteration.clone(),
+            progress.p.clone(),
+            progress.q.clone(),
+        ));
+    }
+}
+
+#[test]
+fn test_progress_updates() {
+    let n = Integer::from(SEMIPRIME);
+    let a0 = sqrt_ceil(&n);
+    let limit = SearchLimit::default();
+    let cancel = CancelToken::new();
+
+    for parallel in [false, true] {
+        let recorder = Recorder::default();
+        let mut iter = Integer::from(1);
+        let outcome = if parallel {
+            bounded_parallel_difference_of_squares(&n, &mut iter, &limit, &cancel, &recorder)
+        } else {
+            bounded_difference_of_squares(&n, &mut iter, &limit, &cancel, &recorder)
+        };
+        let (p, q) = (Integer::from(SEMIPRIME_P), Integer::from(SEMIPRIME_Q));
+        assert_eq!(outcome, FermatOutcome::Found(p.clone(), q.clone()));
+        assert_eq!(*recorder.found.borrow(), [(iter, p, q)]);
+
+        let updates = recorder.updates.borrow();
+        if !parallel {
+            assert!(updates.len() as u64 >= SEMIPRIME_ITERATION / PROGRESS_INTERVAL);
+        }
+        assert!(!updates.is_empty());
+        for (iteration, a, p, q, _) in updates.iter() {
+            assert_eq!(*a, &a0 + iteration - Integer::from(1));
+            // p and q straddle a by ⌊√(a² − n)⌋
+            assert_eq!(p + q, Integer::from(2) * a);
+            let x = (q - p) >> 1u32;
+            let y = &x + Integer::from(1);
+            let x2 = a * a - &n;
+            assert!(&x * &x <= x2 && &y * &y > x2);
+        }
+        for pair in updates.windows(2) {
+            assert!(pair[0].0 < pair[1].0);
+            assert!(pair[0].4 <= pair[1].4);
+        }
+    }
+}
+
+#[test]
+fn test_immediate_hit_reports_once() {
+    // 102² − 101 · 103 = 1², so the very first candidate splits n
+    let n = Integer::from(101 * 103);
+    for parallel in [false, true] {
+        let recorder = Recorder::default();
+        let mut iter = Integer::from(1);
+        let (limit, cancel) = (SearchLimit::default(), CancelToken::new());
+        let outcome = if parallel {
+            bounded_parallel_difference_of_squares(&n, &mut iter, &limit, &cancel, &recorder)
+        } else {
+            bounded_difference_of_squares(&n, &mut iter, &limit, &cancel, &recorder)
+        };
+        assert_eq!(
+            outcome,
+            FermatOutcome::Found(Integer::from(101), Integer::from(103))
+        );
+        assert!(recorder.updates.borrow().is_empty());
+        assert_eq!(recorder.found.borrow().len(), 1);
+    }
+}
+
+#[test]
+fn test_ratio_reports_only_proper_split() {
+    // With the exact ratio, 4·101·103·n is a square and the first hit a = 2n splits off n
+    let n = Integer::from(101 * 103);
+    let recorder = Recorder::default();
+    let mut iter = Integer::from(1);
+    let ratio = Ratio { u: 101, v: 103 };
+    let result = ratio_fermat(&n, ratio, &mut iter, &CancelToken::new(), &recorder);
+    assert_eq!(result, Some((Integer::from(101), Integer::from(103))));
+    let found = recorder.found.borrow();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0, iter);
+}
+
+#[test]
+fn test_silent_progress() {
+    let n = Integer::from(5959);
+    let mut iter = Integer::from(1);
+    let outcome = bounded_difference_of_squares(
+        &n,
+        &mut iter,
+        &SearchLimit::default(),
+        &CancelToken::new(),
+        &Silent,
+    );
+    assert_eq!(
+        outcome,
+        FermatOutcome::Found(Integer::from(59), Integer::from(101))
+    );
+}
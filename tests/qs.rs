@@ -0,0 +1,107 @@
+use diffsquare::cancel::CancelToken;
+use diffsquare::qs::factor_base::{select_multiplier, sqrt_mod, FactorBase};
+use diffsquare::qs::linalg::find_dependencies;
+use diffsquare::qs::{quadratic_sieve, Params};
+use malachite::{Integer, Natural};
+
+#[test]
+fn test_sqrt_mod() {
+    for p in [3u64, 5, 13, 17, 41, 97, 7919, 1_000_000_007] {
+        for n in 1..50 {
+            if let Some(r) = sqrt_mod(n, p) {
+                assert_eq!(r as u128 * r as u128 % p as u128, n as u128 % p as u128);
+            }
+        }
+    }
+    assert_eq!(sqrt_mod(3, 7), None);
+}
+
+#[test]
+fn test_factor_base() {
+    let n = Natural::from(129517728360589090325549187487u128);
+    let k = select_multiplier(&n);
+    let fb = FactorBase::new(&n, k, 100);
+
+    assert_eq!(fb.len(), 100);
+    assert_eq!(fb.primes[0], 2);
+    for (&p, &r) in fb.primes.iter().zip(&fb.sqrts).skip(1) {
+        let kn_mod = u64::try_from(&(&fb.kn % Natural::from(p))).unwrap();
+        assert_eq!(r as u128 * r as u128 % p as u128, kn_mod as u128);
+    }
+}
+
+#[test]
+fn test_params_for_digits() {
+    assert_eq!(Params::for_digits(40).fb_size, 350);
+    assert!(Params::for_digits(45).fb_size > Params::for_digits(40).fb_size);
+    assert!(Params::for_digits(45).fb_size < Params::for_digits(50).fb_size);
+    assert_eq!(Params::for_digits(120), Params::for_digits(100));
+}
+
+#[test]
+fn test_find_dependencies() {
+    // The null space is spanned by {0, 1, 2} and {3}; row 4 has singleton columns.
+    let rows = vec![vec![0, 1], vec![1, 2], vec![0, 2], vec![], vec![3, 4]];
+    let deps = find_dependencies(&rows, 8);
+
+    assert_eq!(deps.len(), 2);
+    for dep in &deps {
+        assert!(!dep.is_empty() && !dep.contains(&4));
+        let mut parity = [false; 5];
+        for &r in dep {
+            for &c in &rows[r] {
+                parity[c] ^= true;
+            }
+        }
+        assert!(parity.iter().all(|odd| !odd));
+    }
+}
+
+#[test]
+fn test_quadratic_sieve() {
+    let p = Integer::from(235002249241657u64);
+    let q = Integer::from(551133994583191u64);
+    let mut iter = Integer::from(0);
+
+    let result = quadratic_sieve(&(&p * &q), &mut iter, &CancelToken::new());
+
+    assert_eq!(result, Some((p, q)));
+    assert!(iter > 0);
+}
+
+#[test]
+fn test_quadratic_sieve_40_digits() {
+    let p = Integer::from(83505277673685798443u128);
+    let q = Integer::from(89402481325338331121u128);
+    let mut iter = Integer::from(0);
+
+    assert_eq!(
+        quadratic_sieve(&(&p * &q), &mut iter, &CancelToken::new()),
+        Some((p, q))
+    );
+}
+
+#[test]
+fn test_quadratic_sieve_trivial() {
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        quadratic_sieve(&Integer::from(97), &mut iter, &CancelToken::new()),
+        None
+    );
+    assert_eq!(
+        quadratic_sieve(
+            &Integer::from(1_000_003u64 * 1_000_003),
+            &mut iter,
+            &CancelToken::new()
+        ),
+        Some((Integer::from(1_000_003), Integer::from(1_000_003)))
+    );
+    assert_eq!(
+        quadratic_sieve(
+            &Integer::from(2 * 1_000_003u64),
+            &mut iter,
+            &CancelToken::new()
+        ),
+        Some((Integer::from(2), Integer::from(1_000_003)))
+    );
+}
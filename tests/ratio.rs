@@ -0,0 +1,95 @@
+use diffsquare::cancel::CancelToken;
+use diffsquare::progress::Silent;
+use diffsquare::ratio::{auto_ratio_fermat, ratio_fermat, ratios_up_to, Ratio};
+use malachite::Integer;
+
+fn three_to_five() -> (Integer, Integer) {
+    // q ≈ 5p/3: Fermat on n alone would need about 10^14 iterations
+    (
+        Integer::from(1000000000000037u64),
+        Integer::from(1666666666666733u64),
+    )
+}
+
+#[test]
+fn test_ratio_parse() {
+    assert_eq!("3/5".parse::<Ratio>(), Ok(Ratio { u: 3, v: 5 }));
+    assert_eq!(Ratio { u: 3, v: 5 }.to_string(), "3/5");
+    assert!("3".parse::<Ratio>().is_err());
+    assert!("0/5".parse::<Ratio>().is_err());
+    assert!("a/b".parse::<Ratio>().is_err());
+}
+
+#[test]
+fn test_ratio_multiplier() {
+    assert_eq!(Ratio { u: 3, v: 5 }.multiplier(), 60);
+    let huge = Ratio {
+        u: u64::MAX,
+        v: u64::MAX - 1,
+    };
+    assert_eq!(
+        huge.multiplier(),
+        Integer::from(4) * Integer::from(u64::MAX) * Integer::from(u64::MAX - 1)
+    );
+
+    // A ratio whose 4·u·v overflows u64 must not panic
+    let cancelled = CancelToken::new();
+    cancelled.cancel();
+    let (p, q) = three_to_five();
+    let mut iter = Integer::from(1);
+    assert_eq!(
+        ratio_fermat(&(&p * &q), huge, &mut iter, &cancelled, &Silent),
+        None
+    );
+}
+
+#[test]
+fn test_ratios_up_to() {
+    let ratios: Vec<String> = ratios_up_to(3).iter().map(|r| r.to_string()).collect();
+    assert_eq!(ratios, ["1/1", "1/2", "1/3", "2/3"]);
+    assert_eq!(ratios_up_to(10).len(), 32);
+}
+
+#[test]
+fn test_ratio_fermat() {
+    let (p, q) = three_to_five();
+    let n = &p * &q;
+
+    for ratio in [Ratio { u: 3, v: 5 }, Ratio { u: 5, v: 3 }] {
+        let mut iter = Integer::from(1);
+        let result = ratio_fermat(&n, ratio, &mut iter, &CancelToken::new(), &Silent);
+        assert_eq!(result, Some((p.clone(), q.clone())));
+        assert!(iter < 10);
+    }
+}
+
+#[test]
+fn test_auto_ratio_fermat() {
+    let (p, q) = three_to_five();
+    let mut iter = Integer::from(0);
+
+    let result = auto_ratio_fermat(&(&p * &q), 6, &mut iter, &CancelToken::new());
+
+    assert_eq!(result, Some((p, q)));
+    assert!(iter > 0 && iter < 10);
+}
+
+#[test]
+fn test_ratio_fermat_prime() {
+    let mut iter = Integer::from(1);
+    let n = Integer::from(1000000007);
+    assert_eq!(
+        ratio_fermat(
+            &n,
+            Ratio { u: 1, v: 2 },
+            &mut iter,
+            &CancelToken::new(),
+            &Silent
+        ),
+        None
+    );
+    assert_eq!(
+        auto_ratio_fermat(&n, 4, &mut iter, &CancelToken::new()),
+        None
+    );
+}
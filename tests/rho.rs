@@ -0,0 +1,55 @@
+use diffsquare::cancel::CancelToken;
+use diffsquare::rho::pollard_rho;
+use malachite::Integer;
+
+#[test]
+fn test_pollard_rho_small_factor() {
+    // 2^64 + 1 = 274177 · 67280421310721
+    let n = (Integer::from(1) << 64u32) + Integer::from(1);
+    let mut iter = Integer::from(0);
+
+    let result = pollard_rho(&n, 2, &mut iter, &CancelToken::new());
+
+    assert_eq!(
+        result,
+        Some((Integer::from(274177), Integer::from(67280421310721u64)))
+    );
+    assert!(iter > 0);
+}
+
+#[test]
+fn test_pollard_rho_unbalanced() {
+    // 1000003 · (2^61 - 1): hopeless for Fermat, quick for rho
+    let p = Integer::from(1000003);
+    let q = Integer::from(2305843009213693951u64);
+    let n = &p * &q;
+
+    for seed in [2u64, 3, 12345] {
+        let mut iter = Integer::from(0);
+        let result = pollard_rho(&n, seed, &mut iter, &CancelToken::new());
+        assert_eq!(
+            result,
+            Some((p.clone(), q.clone())),
+            "Seed {} did not split {}",
+            seed,
+            n
+        );
+    }
+}
+
+#[test]
+fn test_pollard_rho_trivial() {
+    let mut iter = Integer::from(0);
+    assert_eq!(
+        pollard_rho(&Integer::from(1), 2, &mut iter, &CancelToken::new()),
+        None
+    );
+    assert_eq!(
+        pollard_rho(&Integer::from(101), 2, &mut iter, &CancelToken::new()),
+        None
+    );
+    assert_eq!(
+        pollard_rho(&Integer::from(5959 * 2), 2, &mut iter, &CancelToken::new()),
+        Some((Integer::from(2), Integer::from(5959)))
+    );
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use common::SEMIPRIME;
+use diffsquare::cancel::CancelToken;
+use diffsquare::factor::{SearchLimit, Searched};
+use diffsquare::progress::Silent;
+use diffsquare::ratio::Ratio;
+use diffsquare::split::{split, FactorOutcome, Failure, Method};
+use diffsquare::sqrt::sqrt_ceil;
+use malachite::Integer;
+use std::time::Instant;
+
+fn run(n: u64, method: Method, start: u64, cancel: &CancelToken) -> FactorOutcome {
+    let limit = SearchLimit::default();
+    split(
+        &Integer::from(n),
+        method,
+        &Integer::from(start),
+        &limit,
+        cancel,
+        &Silent,
+    )
+}
+
+#[test]
+fn test_split_found() {
+    let cancel = CancelToken::new();
+    let outcome = run(5959, Method::Fermat, 1, &cancel);
+    assert_eq!(outcome.result, Ok((Integer::from(59), Integer::from(101))));
+    assert_eq!(outcome.method, Method::Fermat);
+    assert_eq!(outcome.iterations, 3);
+
+    let ratio = Method::RatioFermat(Ratio { u: 1, v: 1 });
+    assert_eq!(
+        run(5959, ratio, 1, &cancel).found(),
+        Some((Integer::from(59), Integer::from(101)))
+    );
+
+    // The start offset only applies to Fermat; rho counts from zero.
+    let n = 1000003u64 * 1000033;
+    let outcome = run(n, Method::Rho { seed: 2 }, 1_000_000, &cancel);
+    assert!(outcome.iterations < 1_000_000);
+    assert_eq!(
+        outcome.found(),
+        Some((Integer::from(1000003), Integer::from(1000033)))
+    );
+}
+
+#[test]
+fn test_split_failures() {
+    let cancel = CancelToken::new();
+    let fail = |n: u64, method: Method| run(n, method, 1, &cancel).result.unwrap_err();
+
+    assert_eq!(fail(1, Method::Fermat), Failure::TooSmall);
+    assert_eq!(fail(3, Method::Qs), Failure::Prime);
+    assert_eq!(fail(65537, Method::Hart), Failure::Prime);
+    assert_eq!(fail(2000000014, Method::Fermat), Failure::EvenInput);
+    assert_eq!(
+        fail(2000000014, Method::AutoRatioFermat(3)),
+        Failure::EvenInput
+    );
+    assert_eq!(
+        run(2000000014, Method::Rho { seed: 2 }, 1, &cancel).found(),
+        Some((Integer::from(2), Integer::from(1000000007)))
+    );
+    assert_eq!(
+        fail(1000003u64 * 1000033, Method::Pm1 { b1: 10, b2: 0 }),
+        Failure::NotFound
+    );
+
+    let limit = SearchLimit {
+        max_iterations: Some(Integer::from(100)),
+        max_gap_bits: None,
+    };
+    let outcome = split(
+        &Integer::from(SEMIPRIME),
+        Method::Fermat,
+        &Integer::from(1),
+        &limit,
+        &cancel,
+        &Silent,
+    );
+    assert_eq!(
+        outcome.result,
+        Err(Failure::Exhausted {
+            searched: Searched {
+                start: sqrt_ceil(&Integer::from(SEMIPRIME)),
+                end: sqrt_ceil(&Integer::from(SEMIPRIME)) + Integer::from(100),
+                min_gap: Some(Integer::from(1132016))
+            }
+        })
+    );
+    assert_eq!(outcome.iterations, 101);
+    assert_eq!(
+        outcome.result.unwrap_err().min_gap(),
+        Some(&Integer::from(1132016))
+    );
+
+    // Resuming at iteration 101 alone claims nothing about the first hundred a values
+    let n = Integer::from(SEMIPRIME);
+    let limit = SearchLimit {
+        max_iterations: Some(Integer::from(200)),
+        max_gap_bits: None,
+    };
+    let outcome = split(
+        &n,
+        Method::Fermat,
+        &Integer::from(101),
+        &limit,
+        &cancel,
+        &Silent,
+    );
+    let failure = outcome.result.unwrap_err();
+    assert_eq!(failure.min_gap(), None);
+    assert_eq!(
+        failure.to_string(),
+        format!(
+            "search limit reached, searched a ∈ [{}, {})",
+            sqrt_ceil(&n) + Integer::from(100),
+            sqrt_ceil(&n) + Integer::from(200)
+        )
+    );
+
+    // Resumed from a run that began at ⌈√n⌉, the whole range and its bound come back
+    let failure = failure.resumed_from(&n, &sqrt_ceil(&n));
+    assert_eq!(failure.searched().unwrap().start, sqrt_ceil(&n));
+    let whole = split(
+        &n,
+        Method::Fermat,
+        &Integer::from(1),
+        &limit,
+        &cancel,
+        &Silent,
+    );
+    assert_eq!(Err(failure), whole.result);
+}
+
+#[test]
+fn test_split_cancelled_and_timed_out() {
+    let cancelled = CancelToken::new();
+    cancelled.cancel();
+    let expired = CancelToken::with_deadline(Instant::now());
+
+    // 2·⌈√(78² − 5959)⌉ = 24 before anything has run
+    let searched = Some(Searched {
+        start: Integer::from(78),
+        end: Integer::from(78),
+        min_gap: Some(Integer::from(24)),
+    });
+    assert_eq!(
+        run(5959, Method::Fermat, 1, &cancelled).result,
+        Err(Failure::Cancelled {
+            searched: searched.clone()
+        })
+    );
+    assert_eq!(
+        run(5959, Method::Fermat, 1, &expired).result,
+        Err(Failure::TimedOut { searched })
+    );
+
+    let n = 1000003u64 * 1000033;
+    let failure = run(n, Method::Rho { seed: 2 }, 0, &expired)
+        .result
+        .unwrap_err();
+    assert_eq!(failure, Failure::TimedOut { searched: None });
+    assert_eq!(failure.code(), "timed_out");
+    assert_eq!(
+        run(n, Method::Hart, 0, &cancelled).result,
+        Err(Failure::Cancelled { searched: None })
+    );
+}
@@ -0,0 +1,62 @@
+use diffsquare::factor::factorize_with;
+use diffsquare::squfof::{isqrt, squfof};
+use malachite::Integer;
+
+#[test]
+fn test_isqrt() {
+    assert_eq!(isqrt(0), 0);
+    assert_eq!(isqrt(15), 3);
+    assert_eq!(isqrt(16), 4);
+    assert_eq!(isqrt(u64::MAX as u128), u32::MAX as u128);
+    assert_eq!(isqrt((1u128 << 100) - 1), (1u128 << 50) - 1);
+}
+
+#[test]
+fn test_squfof() {
+    // Products of two 31-bit primes, plus unbalanced and near-limit cases
+    let numbers: Vec<u64> = vec![
+        2761929023323646159,
+        3189046231347719467,
+        3234246546378360389,
+        3869305776707280953,
+        4357328471891213977,
+        11111 * 1_000_003,
+        4_294_967_291 * 1_073_741_789,
+        1_000_000_007 * 1_000_000_009,
+    ];
+
+    for n in numbers {
+        let f = squfof(n).unwrap_or_else(|| panic!("no factor for {n}"));
+        assert!(f > 1 && f < n && n % f == 0, "bad factor {f} of {n}");
+    }
+}
+
+#[test]
+fn test_squfof_trivial() {
+    assert_eq!(squfof(1), None);
+    assert_eq!(squfof(65537), None);
+    assert_eq!(squfof(1 << 62), None);
+    assert_eq!(squfof(1_000_003 * 1_000_003), Some(1_000_003));
+    assert_eq!(squfof(2 * 1_000_003), Some(2));
+}
+
+#[test]
+fn test_factorize_uses_squfof() {
+    // The splitter never succeeds, so every split below 2^62 must come from SQUFOF.
+    let n = Integer::from(1_000_000_007u64 * 1_000_000_009);
+    let mut iterations = Integer::from(0);
+
+    let mut unfactored = Vec::new();
+
+    let factors = factorize_with(&n, &mut iterations, &mut unfactored, |_, _| None);
+
+    assert_eq!(
+        factors,
+        vec![
+            (Integer::from(1_000_000_007), 1),
+            (Integer::from(1_000_000_009), 1)
+        ]
+    );
+    assert_eq!(iterations, 0);
+    assert!(unfactored.is_empty());
+}
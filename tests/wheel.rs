@@ -0,0 +1,50 @@
+use diffsquare::wheel::{Wheel, WHEEL_PRIMES};
+use malachite::Integer;
+
+#[test]
+fn test_wheel_shape() {
+    let wheel = Wheel::new(&Integer::from(1050000018350000077u64), WHEEL_PRIMES);
+
+    assert_eq!(wheel.modulus, 15015);
+    assert_eq!(wheel.residues.len(), wheel.increments.len());
+    assert_eq!(wheel.increments.iter().sum::<u64>(), wheel.modulus);
+    assert!(wheel.residues.windows(2).all(|w| w[0] < w[1]));
+    // Fewer than 7% of all residues survive
+    assert!((wheel.residues.len() as u64) * 100 < wheel.modulus * 7);
+}
+
+#[test]
+fn test_wheel_keeps_every_square() {
+    // Every a with a² − n a perfect square must be admissible
+    for n in [5959u64, 1022117, 999962000357, 15015 * 7 + 1] {
+        let wheel = Wheel::new(&Integer::from(n), WHEEL_PRIMES);
+        let root = (n as f64).sqrt().ceil() as u64;
+        for a in root..root + 50_000 {
+            let x2 = a * a - n;
+            let x = (x2 as f64).sqrt() as u64;
+            if (x.saturating_sub(1)..=x + 1).any(|y| y * y == x2) {
+                assert!(
+                    wheel.residues.contains(&(a % wheel.modulus)),
+                    "a = {a} dropped for n = {n}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_wheel_start_and_advance() {
+    let wheel = Wheel::new(&Integer::from(5959), WHEEL_PRIMES);
+    let last = *wheel.residues.last().unwrap();
+
+    assert_eq!(wheel.start(&Integer::from(wheel.residues[3])), (3, 0));
+    assert_eq!(
+        wheel.start(&Integer::from(wheel.modulus + last + 1)),
+        (0, wheel.modulus - last - 1 + wheel.residues[0])
+    );
+
+    let mut index = wheel.residues.len() - 1;
+    let d = wheel.advance(&mut index);
+    assert_eq!(index, 0);
+    assert_eq!((last + d) % wheel.modulus, wheel.residues[0]);
+}